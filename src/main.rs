@@ -1,5 +1,5 @@
 use std::io::{ErrorKind, Read, Write};
-use std::net::{Shutdown, TcpListener, TcpStream};
+use std::net::{TcpListener, TcpStream};
 use http::consts::StatusCode;
 
 fn main() {
@@ -40,7 +40,7 @@ fn main() {
 			}
 		}
 
-		let request = match partial_request.into_request() {
+		let _request = match partial_request.into_request() {
 			Ok(v) => v,
 			Err(e) => {
 				eprintln!("http request parse error: {e:#?}");
@@ -58,7 +58,8 @@ fn main() {
 
 		let bytes = builder
 			.into_response()
-			.into_bytes();
+			.into_bytes()
+			.unwrap();
 
 		// tcp_stream.write_all(b"HTTP/1.1 200 OK\r\n\r\n").unwrap()
 		tcp_stream.write_all(bytes.as_slice()).unwrap();
@@ -72,7 +73,8 @@ fn main() {
 			match tcp_stream.read(&mut buff) {
 				Ok(n) => {
 					if n == 0 {
-						continue 'accept_connection;
+						partial_response.signal_connection_close();
+						break 'collect_response;
 					}
 
 					println!("pushing bytes: {:?}", String::from_utf8_lossy(&buff[..n]));
@@ -105,6 +107,7 @@ fn main() {
 	}
 }
 
+#[allow(dead_code)]
 fn drain_tcp(tcp_stream: &mut TcpStream) {
 	// could be also a blocking read with timeout
 	tcp_stream.set_nonblocking(true).unwrap();
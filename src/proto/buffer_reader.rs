@@ -30,20 +30,17 @@ impl DelayedStateBuffer {
 
 	pub fn take_byte(&mut self, buffer: &[u8]) -> Option<u8> {
 		self.seek_byte(buffer)
-			.map(|b| {
+			.inspect(|_| {
 				self.current_read_head += 1;
-				b
 			})
 	}
 
+	#[allow(dead_code)]
 	pub fn take_whitespace<'a>(&mut self, buffer: &'a [u8]) -> DelayedConsumeResult<'a> {
 		let mut wh_len = 0usize;
 		loop {
 			match self.seek_byte(buffer) {
 				Some(b) => {
-					if !b.is_ascii() {
-						panic!("not ascii character in sequence");
-					}
 					if b.is_ascii_whitespace() {
 						wh_len += 1;
 						self.take_byte(buffer);
@@ -53,7 +50,7 @@ impl DelayedStateBuffer {
 						return Finished {
 							base_index: tmp_consumed,
 							consumed: wh_len,
-							slice: &buffer[tmp_consumed..wh_len],
+							slice: &buffer[tmp_consumed..self.current_read_head],
 						};
 					}
 				}
@@ -64,10 +61,6 @@ impl DelayedStateBuffer {
 
 	pub fn take_line<'a>(&mut self, buffer: &'a [u8]) -> DelayedConsumeResult<'a> {
 		while let Some(b) = self.take_byte(buffer) {
-			if !b.is_ascii() {
-				panic!("Non-ascii character in sequence");
-			}
-
 			if b == b'\n' {
 				let slice = &buffer[self.n_bytes_consumed..self.current_read_head - 1];
 				let slice = slice.strip_suffix(b"\r").unwrap_or(slice);
@@ -88,7 +81,7 @@ impl DelayedStateBuffer {
 	pub fn take_exact<'a>(&mut self, buffer: &'a [u8], length: usize) -> DelayedConsumeResult<'a> {
 		if self.n_bytes_consumed + length <= buffer.len() {
 			let tmp_consumed = self.n_bytes_consumed;
-			self.n_bytes_consumed = self.n_bytes_consumed + length;
+			self.n_bytes_consumed += length;
 			self.current_read_head = self.n_bytes_consumed;
 			Finished {
 				base_index: tmp_consumed,
@@ -102,9 +95,42 @@ impl DelayedStateBuffer {
 }
 
 impl DelayedStateBuffer {
+	pub fn take_all<'a>(&mut self, buffer: &'a [u8]) -> DelayedConsumeResult<'a> {
+		let length = buffer.len() - self.n_bytes_consumed;
+		self.take_exact(buffer, length)
+	}
+
+	/** Whatever is available, but no more than `max_length`; nothing available is NotEnoughBytes */
+	pub fn take_up_to<'a>(&mut self, buffer: &'a [u8], max_length: usize) -> DelayedConsumeResult<'a> {
+		let length = max_length.min(buffer.len() - self.n_bytes_consumed);
+		if length == 0 && max_length != 0 {
+			return NotEnoughBytes;
+		}
+		self.take_exact(buffer, length)
+	}
+
+	/** For when the owner drops the consumed bytes from the front of its buffer */
+	pub fn forget_consumed(&mut self) -> usize {
+		let consumed = self.n_bytes_consumed;
+		self.n_bytes_consumed = 0;
+		self.current_read_head -= consumed;
+		consumed
+	}
+}
+
+impl DelayedStateBuffer {
+	/** A reader for what follows in the same buffer, starting where this one stopped consuming */
+	pub fn following(&self) -> Self {
+		Self {
+			current_read_head: self.n_bytes_consumed,
+			n_bytes_consumed: self.n_bytes_consumed,
+		}
+	}
+
 	pub fn consumed(&self) -> usize {
 		self.n_bytes_consumed
 	}
+	#[allow(dead_code)]
 	pub fn current_read_head(&self) -> usize {
 		self.current_read_head
 	}
@@ -112,7 +138,7 @@ impl DelayedStateBuffer {
 
 #[test]
 fn test_buffer_read() {
-	let mut internal_buffer = Vec::<u8>::from(
+	let internal_buffer = Vec::<u8>::from(
 		b"HTTP/1.1 200 OK\r\n\
 			host: unstd.pl\r\n\r\n"
 	);
@@ -0,0 +1,109 @@
+/// Ceilings on what a collector buffers for a single message, so that a peer
+/// can't make it grow without bound. Every limit has its own error kind, see
+/// [`ParseError::status_code`](crate::ParseError::status_code) for what to reply with.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CollectorLimits {
+	/** request or status line, CRLF excluded */
+	pub max_first_line: usize,
+	/** a single header or trailer line, CRLF excluded */
+	pub max_header_line: usize,
+	/** header fields, trailers counted separately */
+	pub max_header_count: usize,
+	/** first line and header section together, line endings included */
+	pub max_preamble: usize,
+	/** a chunk size line, chunk extensions included, CRLF excluded */
+	pub max_chunk_line: usize,
+	/** decoded body bytes, including those already taken with `take_body` */
+	pub max_body: usize,
+	/** 1xx responses ahead of the final one */
+	pub max_interim_responses: usize,
+}
+
+impl Default for CollectorLimits {
+	/** Header limits in line with common servers; the body isn't limited */
+	fn default() -> Self {
+		Self {
+			max_first_line: 8 * 1024,
+			max_header_line: 8 * 1024,
+			max_header_count: 100,
+			max_preamble: 64 * 1024,
+			// next to nothing uses chunk extensions
+			max_chunk_line: 1024,
+			max_body: usize::MAX,
+			max_interim_responses: 16,
+		}
+	}
+}
+
+impl CollectorLimits {
+	pub fn unlimited() -> Self {
+		Self {
+			max_first_line: usize::MAX,
+			max_header_line: usize::MAX,
+			max_header_count: usize::MAX,
+			max_preamble: usize::MAX,
+			max_chunk_line: usize::MAX,
+			max_body: usize::MAX,
+			max_interim_responses: usize::MAX,
+		}
+	}
+}
+
+/// Which deviations from RFC 9112 a collector puts up with. Each of them has
+/// been used to make two parsers disagree on where a message ends, so requests
+/// are collected strictly by default.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ParserConfig {
+	/** lines ended by LF alone */
+	pub allow_bare_lf: bool,
+	/** runs of SP, HTAB, VT or FF between the parts of the first line, and after it */
+	pub allow_whitespace_separators: bool,
+	/** a status line without the SP after the code */
+	pub allow_missing_reason_phrase: bool,
+	/** `http/1.1` and alike */
+	pub allow_lowercase_version: bool,
+	/** obs-fold, continuation lines starting with SP or HTAB */
+	pub allow_obs_fold: bool,
+	/** trailing whitespace in a field name, `Name : value`; it's dropped */
+	pub allow_whitespace_before_colon: bool,
+	/** both Content-Length and Transfer-Encoding, the latter wins */
+	pub allow_content_length_with_transfer_encoding: bool,
+	/** Transfer-Encoding in an HTTP/1.0 message */
+	pub allow_transfer_encoding_in_http_1_0: bool,
+}
+
+impl ParserConfig {
+	/** RFC 9112 to the letter */
+	pub fn strict() -> Self {
+		Self {
+			allow_bare_lf: false,
+			allow_whitespace_separators: false,
+			allow_missing_reason_phrase: false,
+			allow_lowercase_version: false,
+			allow_obs_fold: false,
+			allow_whitespace_before_colon: false,
+			allow_content_length_with_transfer_encoding: false,
+			allow_transfer_encoding_in_http_1_0: false,
+		}
+	}
+
+	/** For peers known to be sloppy, every leniency on */
+	pub fn lenient() -> Self {
+		Self {
+			allow_bare_lf: true,
+			allow_whitespace_separators: true,
+			allow_missing_reason_phrase: true,
+			allow_lowercase_version: true,
+			allow_obs_fold: true,
+			allow_whitespace_before_colon: true,
+			allow_content_length_with_transfer_encoding: true,
+			allow_transfer_encoding_in_http_1_0: true,
+		}
+	}
+}
+
+impl Default for ParserConfig {
+	fn default() -> Self {
+		Self::strict()
+	}
+}
@@ -0,0 +1,149 @@
+use crate::config::{CollectorLimits, ParserConfig};
+use crate::request::{Collector, Request};
+use crate::proto::parser::CollectError;
+
+/// Server side of a connection: bytes go in as they are read, requests come
+/// out in order, however the pipelined ones were split across reads.
+///
+/// Iterating yields every request completed so far; `None` only means more
+/// bytes are needed. After an error, or a request with faulty framing let
+/// through by the config, nothing more is yielded and the connection is to be
+/// closed, since where the next request starts is unknown.
+pub struct Connection {
+	collector: Collector,
+	/** pushed while `collector` was finished, i.e. past the end of its request */
+	pending: Vec<u8>,
+	closed: bool,
+}
+
+impl Default for Connection {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Connection {
+	pub fn new() -> Self {
+		Self {
+			collector: Collector::new(),
+			pending: Vec::new(),
+			closed: false,
+		}
+	}
+
+	/** To be set before any bytes are pushed; applies to every request */
+	pub fn with_limits(self, limits: CollectorLimits) -> Self {
+		Self {
+			collector: self.collector.with_limits(limits),
+			..self
+		}
+	}
+
+	/** Strict by default, see [`ParserConfig`]; to be set before any bytes are pushed */
+	pub fn with_config(self, config: ParserConfig) -> Self {
+		Self {
+			collector: self.collector.with_config(config),
+			..self
+		}
+	}
+
+	pub fn push_bytes(&mut self, bytes: &[u8]) {
+		if self.closed {
+			return;
+		}
+		if self.collector.is_finished() {
+			self.pending.extend_from_slice(bytes);
+		} else {
+			self.collector.push_bytes(bytes);
+		}
+	}
+}
+
+impl Iterator for Connection {
+	type Item = Result<Request, CollectError>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.closed || !self.collector.is_finished() {
+			return None;
+		}
+
+		if self.collector.must_close() {
+			self.closed = true;
+			self.pending.clear();
+			let collector = std::mem::take(&mut self.collector);
+			return Some(collector.into_request());
+		}
+
+		// the unparsed rest moves along, each byte is only scanned once
+		let next_collector = self.collector.next_collector();
+		let collector = std::mem::replace(&mut self.collector, next_collector);
+
+		match collector.into_request() {
+			Ok(request) => {
+				self.collector.push_bytes(&self.pending);
+				self.pending.clear();
+				Some(Ok(request))
+			}
+			Err(e) => {
+				self.closed = true;
+				self.pending.clear();
+				Some(Err(e))
+			}
+		}
+	}
+}
+
+#[test]
+fn test_pipelined_requests() {
+	use crate::ParseError;
+
+	let mut connection = Connection::new();
+	connection.push_bytes(b"GET /a HTTP/1.1\r\n\r\nPOST /b HTTP/1.1\r\nContent-Length: 5\r\n\r\nhe");
+	connection.push_bytes(b"llo");
+	connection.push_bytes(b"GET /c HTTP/1.1\r\n");
+
+	let paths: Vec<String> = connection.by_ref()
+		.map(|r| r.unwrap().url().path.clone())
+		.collect();
+	assert_eq!(paths, vec!["/a", "/b"]);
+
+	connection.push_bytes(b"\r\nGET /d HTTP/1.1\r\nHost :x\r\n\r\nGET /e HTTP/1.1\r\n\r\n");
+	assert_eq!(connection.next().unwrap().unwrap().url().path, "/c");
+
+	// offsets count from the start of the connection
+	let e = connection.next().unwrap().unwrap_err();
+	assert_eq!(e.kind(), ParseError::WhitespaceBeforeColon);
+	assert_eq!(e.offset(), 103);
+	assert!(connection.next().is_none());
+
+	// the request itself is fine, what follows it can't be told apart
+	let mut connection = Connection::new().with_config(ParserConfig::lenient());
+	connection.push_bytes(b"POST / HTTP/1.1\r\nContent-Length: 3\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n");
+	connection.push_bytes(b"GET /smuggled HTTP/1.1\r\n\r\n");
+	assert_eq!(connection.next().unwrap().unwrap().url().path, "/");
+	assert!(connection.next().is_none());
+}
+
+#[test]
+fn test_many_pipelined_requests() {
+	const COUNT: usize = 20000;
+
+	let mut input = Vec::new();
+	for i in 0..COUNT {
+		input.extend_from_slice(format!("POST /{} HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello", i).as_bytes());
+	}
+
+	let mut connection = Connection::new();
+	connection.push_bytes(&input);
+	connection.push_bytes(b"GET / HTTP/1.1\r\n");
+
+	let mut n = 0;
+	for request in connection.by_ref() {
+		assert_eq!(request.unwrap().url().path, format!("/{}", n));
+		n += 1;
+	}
+	assert_eq!(n, COUNT);
+
+	connection.push_bytes(b"\r\n");
+	assert_eq!(connection.next().unwrap().unwrap().url().path, "/");
+}
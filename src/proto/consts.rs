@@ -1,31 +1,99 @@
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
-use crate::proto::parser::ParseError;
+use crate::proto::parser::{is_token, ParseError};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum Method {
 	GET,
+	HEAD,
 	POST,
 	PUT,
+	DELETE,
+	CONNECT,
+	OPTIONS,
+	TRACE,
 	PATCH,
-	DELETE
+	/** any other method token, e.g. WebDAV's PROPFIND */
+	Extension(String),
+}
+
+impl Method {
+	pub fn as_str(&self) -> &str {
+		use Method::*;
+		match self {
+			GET => "GET",
+			HEAD => "HEAD",
+			POST => "POST",
+			PUT => "PUT",
+			DELETE => "DELETE",
+			CONNECT => "CONNECT",
+			OPTIONS => "OPTIONS",
+			TRACE => "TRACE",
+			PATCH => "PATCH",
+			Extension(token) => token.as_str(),
+		}
+	}
+
+	/** RFC 9110 9.2.1; nothing is assumed about extension methods */
+	pub fn is_safe(&self) -> bool {
+		use Method::*;
+		matches!(self, GET | HEAD | OPTIONS | TRACE)
+	}
+
+	/** RFC 9110 9.2.2; nothing is assumed about extension methods */
+	pub fn is_idempotent(&self) -> bool {
+		use Method::*;
+		self.is_safe() || matches!(self, PUT | DELETE)
+	}
 }
 
 impl FromStr for Method {
-	type Err = ();
+	type Err = ParseError;
 
+	/** Method names are case-sensitive */
 	fn from_str(s: &str) -> Result<Self, Self::Err> {
 		match s {
 			"GET" => Ok(Method::GET),
+			"HEAD" => Ok(Method::HEAD),
 			"POST" => Ok(Method::POST),
 			"PUT" => Ok(Method::PUT),
-			"PATCH" => Ok(Method::PATCH),
 			"DELETE" => Ok(Method::DELETE),
-			_ => Err(())
+			"CONNECT" => Ok(Method::CONNECT),
+			"OPTIONS" => Ok(Method::OPTIONS),
+			"TRACE" => Ok(Method::TRACE),
+			"PATCH" => Ok(Method::PATCH),
+			_ if is_token(s.as_bytes()) => Ok(Method::Extension(s.to_string())),
+			_ => Err(ParseError::InvalidMethod)
 		}
 	}
 }
 
+impl Display for Method {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+#[test]
+fn test_method() {
+	assert_eq!(Method::from_str("OPTIONS"), Ok(Method::OPTIONS));
+	assert_eq!(
+		Method::from_str("PROPFIND"),
+		Ok(Method::Extension("PROPFIND".to_string()))
+	);
+	assert_eq!(Method::from_str("get"), Ok(Method::Extension("get".to_string())));
+	assert_eq!(Method::from_str(""), Err(ParseError::InvalidMethod));
+	assert_eq!(Method::from_str("GE(T"), Err(ParseError::InvalidMethod));
+
+	assert!(Method::HEAD.is_safe());
+	assert!(!Method::PUT.is_safe());
+	assert!(Method::PUT.is_idempotent());
+	assert!(!Method::POST.is_idempotent());
+	assert!(!Method::PATCH.is_idempotent());
+	assert_eq!(Method::TRACE.to_string(), "TRACE");
+	assert_eq!(Method::Extension("MKCOL".to_string()).to_string(), "MKCOL");
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Version {
@@ -63,39 +131,161 @@ impl Display for Version {
 	}
 }
 
-#[allow(non_camel_case_types)]
-#[derive(Copy, Clone, Debug)]
-pub enum StatusCode {
-	SUCCESS = 200,
-	NOT_FOUND = 404,
-	IM_A_TEAPOT = 418,
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct StatusCode(u16);
+
+macro_rules! status_codes {
+	($(($code:literal, $name:ident, $desc:literal),)*) => {
+		impl StatusCode {
+			$(pub const $name: StatusCode = StatusCode($code);)*
+
+			/** Canonical reason phrase, `None` for codes outside of the IANA registry */
+			pub fn as_desc(&self) -> Option<&'static str> {
+				match self.0 {
+					$($code => Some($desc),)*
+					_ => None,
+				}
+			}
+		}
+	};
+}
+
+status_codes! {
+	(100, CONTINUE, "Continue"),
+	(101, SWITCHING_PROTOCOLS, "Switching Protocols"),
+	(102, PROCESSING, "Processing"),
+	(103, EARLY_HINTS, "Early Hints"),
+
+	(200, OK, "OK"),
+	(201, CREATED, "Created"),
+	(202, ACCEPTED, "Accepted"),
+	(203, NON_AUTHORITATIVE_INFORMATION, "Non-Authoritative Information"),
+	(204, NO_CONTENT, "No Content"),
+	(205, RESET_CONTENT, "Reset Content"),
+	(206, PARTIAL_CONTENT, "Partial Content"),
+	(207, MULTI_STATUS, "Multi-Status"),
+	(208, ALREADY_REPORTED, "Already Reported"),
+	(226, IM_USED, "IM Used"),
+
+	(300, MULTIPLE_CHOICES, "Multiple Choices"),
+	(301, MOVED_PERMANENTLY, "Moved Permanently"),
+	(302, FOUND, "Found"),
+	(303, SEE_OTHER, "See Other"),
+	(304, NOT_MODIFIED, "Not Modified"),
+	(305, USE_PROXY, "Use Proxy"),
+	(307, TEMPORARY_REDIRECT, "Temporary Redirect"),
+	(308, PERMANENT_REDIRECT, "Permanent Redirect"),
+
+	(400, BAD_REQUEST, "Bad Request"),
+	(401, UNAUTHORIZED, "Unauthorized"),
+	(402, PAYMENT_REQUIRED, "Payment Required"),
+	(403, FORBIDDEN, "Forbidden"),
+	(404, NOT_FOUND, "Not Found"),
+	(405, METHOD_NOT_ALLOWED, "Method Not Allowed"),
+	(406, NOT_ACCEPTABLE, "Not Acceptable"),
+	(407, PROXY_AUTHENTICATION_REQUIRED, "Proxy Authentication Required"),
+	(408, REQUEST_TIMEOUT, "Request Timeout"),
+	(409, CONFLICT, "Conflict"),
+	(410, GONE, "Gone"),
+	(411, LENGTH_REQUIRED, "Length Required"),
+	(412, PRECONDITION_FAILED, "Precondition Failed"),
+	(413, CONTENT_TOO_LARGE, "Content Too Large"),
+	(414, URI_TOO_LONG, "URI Too Long"),
+	(415, UNSUPPORTED_MEDIA_TYPE, "Unsupported Media Type"),
+	(416, RANGE_NOT_SATISFIABLE, "Range Not Satisfiable"),
+	(417, EXPECTATION_FAILED, "Expectation Failed"),
+	(418, IM_A_TEAPOT, "I'm a teapot"),
+	(421, MISDIRECTED_REQUEST, "Misdirected Request"),
+	(422, UNPROCESSABLE_CONTENT, "Unprocessable Content"),
+	(423, LOCKED, "Locked"),
+	(424, FAILED_DEPENDENCY, "Failed Dependency"),
+	(425, TOO_EARLY, "Too Early"),
+	(426, UPGRADE_REQUIRED, "Upgrade Required"),
+	(428, PRECONDITION_REQUIRED, "Precondition Required"),
+	(429, TOO_MANY_REQUESTS, "Too Many Requests"),
+	(431, REQUEST_HEADER_FIELDS_TOO_LARGE, "Request Header Fields Too Large"),
+	(451, UNAVAILABLE_FOR_LEGAL_REASONS, "Unavailable For Legal Reasons"),
+
+	(500, INTERNAL_SERVER_ERROR, "Internal Server Error"),
+	(501, NOT_IMPLEMENTED, "Not Implemented"),
+	(502, BAD_GATEWAY, "Bad Gateway"),
+	(503, SERVICE_UNAVAILABLE, "Service Unavailable"),
+	(504, GATEWAY_TIMEOUT, "Gateway Timeout"),
+	(505, HTTP_VERSION_NOT_SUPPORTED, "HTTP Version Not Supported"),
+	(506, VARIANT_ALSO_NEGOTIATES, "Variant Also Negotiates"),
+	(507, INSUFFICIENT_STORAGE, "Insufficient Storage"),
+	(508, LOOP_DETECTED, "Loop Detected"),
+	(510, NOT_EXTENDED, "Not Extended"),
+	(511, NETWORK_AUTHENTICATION_REQUIRED, "Network Authentication Required"),
 }
 
 impl StatusCode {
-	pub fn as_desc(&self) -> &'static str {
-		use StatusCode::*;
-		match self {
-			SUCCESS => "OK",
-			NOT_FOUND => "NOT FOUND",
-			IM_A_TEAPOT => "I'M A TEAPOT",
-		}
+	pub const SUCCESS: StatusCode = StatusCode::OK;
+
+	pub fn as_u16(&self) -> u16 {
+		self.0
+	}
+
+	pub fn is_informational(&self) -> bool {
+		(100..200).contains(&self.0)
+	}
+	pub fn is_success(&self) -> bool {
+		(200..300).contains(&self.0)
+	}
+	pub fn is_redirection(&self) -> bool {
+		(300..400).contains(&self.0)
+	}
+	pub fn is_client_error(&self) -> bool {
+		(400..500).contains(&self.0)
+	}
+	pub fn is_server_error(&self) -> bool {
+		(500..600).contains(&self.0)
 	}
 }
 
 impl TryFrom<u32> for StatusCode {
 	type Error = ParseError;
 
+	/** Any three-digit code is accepted, registered or not */
 	fn try_from(value: u32) -> Result<Self, Self::Error> {
-		use StatusCode::*;
 		match value {
-			200 => Ok(SUCCESS),
-			404 => Ok(NOT_FOUND),
-			418 => Ok(IM_A_TEAPOT),
+			100..=999 => Ok(StatusCode(value as u16)),
 			_ => Err(ParseError::InvalidStatusCode)
 		}
 	}
 }
 
+impl TryFrom<u16> for StatusCode {
+	type Error = ParseError;
+
+	fn try_from(value: u16) -> Result<Self, Self::Error> {
+		StatusCode::try_from(value as u32)
+	}
+}
+
+impl Display for StatusCode {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.0)
+	}
+}
+
+#[test]
+fn test_status_code() {
+	assert_eq!(StatusCode::try_from(206u32), Ok(StatusCode::PARTIAL_CONTENT));
+	assert_eq!(StatusCode::try_from(599u32).map(|s| s.as_desc()), Ok(None));
+	assert_eq!(StatusCode::try_from(99u32), Err(ParseError::InvalidStatusCode));
+	assert_eq!(StatusCode::try_from(1000u32), Err(ParseError::InvalidStatusCode));
+
+	assert_eq!(StatusCode::MOVED_PERMANENTLY.as_desc(), Some("Moved Permanently"));
+	assert!(StatusCode::MOVED_PERMANENTLY.is_redirection());
+	assert!(StatusCode::SUCCESS.is_success());
+	assert!(StatusCode::IM_A_TEAPOT.is_client_error());
+	assert!(!StatusCode::BAD_GATEWAY.is_client_error());
+	assert!(StatusCode::BAD_GATEWAY.is_server_error());
+	assert!(StatusCode::EARLY_HINTS.is_informational());
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum MimeType {
 	Unspecified,
 	Multipart,
@@ -0,0 +1,132 @@
+use crate::proto::message::{Message, MessageBuilder};
+use crate::url::percent_encoding::hex_value;
+
+pub const CONTENT_TYPE: &str = "application/x-www-form-urlencoded";
+
+/** `+` is a space, malformed `%` escapes are kept as they are */
+pub(crate) fn decode_component(input: &[u8]) -> String {
+	let mut out = Vec::with_capacity(input.len());
+	let mut i = 0;
+	while i < input.len() {
+		match input[i] {
+			b'+' => out.push(b' '),
+			b'%' => match (input.get(i + 1).and_then(|c| hex_value(*c)),
+						   input.get(i + 2).and_then(|c| hex_value(*c))) {
+				(Some(h), Some(l)) => {
+					out.push(h << 4 | l);
+					i += 2;
+				}
+				_ => out.push(b'%'),
+			},
+			c => out.push(c),
+		}
+		i += 1;
+	}
+	String::from_utf8_lossy(&out).to_string()
+}
+
+pub(crate) fn encode_component(input: &str, out: &mut String) {
+	for c in input.bytes() {
+		match c {
+			b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'*' | b'-' | b'.' | b'_' => out.push(c as char),
+			b' ' => out.push('+'),
+			_ => out.push_str(&format!("%{c:02X}")),
+		}
+	}
+}
+
+/** Name/value pairs in order, repeated names included */
+pub fn decode(input: &[u8]) -> Vec<(String, String)> {
+	input
+		.split(|b| *b == b'&')
+		.filter(|pair| !pair.is_empty())
+		.map(|pair| match pair.iter().position(|b| *b == b'=') {
+			Some(i) => (decode_component(&pair[..i]), decode_component(&pair[i + 1..])),
+			None => (decode_component(pair), String::new()),
+		})
+		.collect()
+}
+
+pub fn encode<'a, I>(pairs: I) -> String
+where
+	I: IntoIterator<Item=(&'a str, &'a str)>,
+{
+	let mut out = String::new();
+	for (name, value) in pairs {
+		if !out.is_empty() {
+			out.push('&');
+		}
+		encode_component(name, &mut out);
+		out.push('=');
+		encode_component(value, &mut out);
+	}
+	out
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FormBuilder {
+	pairs: Vec<(String, String)>,
+}
+
+impl FormBuilder {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn append(&mut self, name: &str, value: &str) -> &mut Self {
+		self.pairs.push((name.to_string(), value.to_string()));
+		self
+	}
+
+	pub fn encode(&self) -> String {
+		encode(self.pairs.iter().map(|(n, v)| (n.as_str(), v.as_str())))
+	}
+}
+
+impl MessageBuilder {
+	/** Sets both the encoded body and its Content-Type */
+	pub fn set_form(&mut self, form: &FormBuilder) -> &mut Self {
+		self.set_header("Content-Type", CONTENT_TYPE);
+		self.set_body(form.encode().as_bytes())
+	}
+}
+
+impl Message {
+	/** Decoded body, `None` unless Content-Type says it's urlencoded */
+	pub fn form(&self) -> Option<Vec<(String, String)>> {
+		let content_type = self.content_type()?;
+		if content_type.essence() != CONTENT_TYPE {
+			return None;
+		}
+		Some(decode(self.body()))
+	}
+}
+
+#[test]
+fn test_form_decode() {
+	assert_eq!(
+		decode(b"tag=a&name=John+Doe&tag=b%26c&&empty=&flag&bad=%zz%4"),
+		vec![
+			("tag".to_string(), "a".to_string()),
+			("name".to_string(), "John Doe".to_string()),
+			("tag".to_string(), "b&c".to_string()),
+			("empty".to_string(), "".to_string()),
+			("flag".to_string(), "".to_string()),
+			("bad".to_string(), "%zz%4".to_string()),
+		]
+	);
+	assert_eq!(decode(b"q=%C5%BC%C3%B3%C5%82w"), vec![("q".to_string(), "żółw".to_string())]);
+}
+
+#[test]
+fn test_form_encode() {
+	let mut form = FormBuilder::new();
+	form.append("name", "John Doe").append("q", "a+b&c=d/é").append("tag", "");
+	assert_eq!(form.encode(), "name=John+Doe&q=a%2Bb%26c%3Dd%2F%C3%A9&tag=");
+
+	let encoded = form.encode();
+	assert_eq!(
+		decode(encoded.as_bytes()),
+		form.pairs
+	);
+}
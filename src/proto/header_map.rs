@@ -0,0 +1,218 @@
+/// Header fields in the order they were added, names compared case-insensitively.
+///
+/// Duplicates are all kept; `get` follows the "keep last" rule and returns
+/// the most recent value, `get_all` returns every one of them in order.
+///
+/// Values that aren't valid UTF-8 (obs-text) are seen through `get` with
+/// replacement characters, the `*_bytes` accessors give them as received.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct HeaderMap {
+	entries: Vec<Field>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Field {
+	name: String,
+	value: String,
+	/** only kept when `value` is a lossy conversion */
+	raw_value: Option<Vec<u8>>,
+}
+
+impl Field {
+	fn new(name: &str, value: &str) -> Self {
+		Self {
+			name: name.to_string(),
+			value: value.to_string(),
+			raw_value: None,
+		}
+	}
+
+	fn value_bytes(&self) -> &[u8] {
+		self.raw_value.as_deref().unwrap_or(self.value.as_bytes())
+	}
+
+	fn as_pair(&self) -> (&str, &str) {
+		(self.name.as_str(), self.value.as_str())
+	}
+}
+
+impl HeaderMap {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	pub fn contains(&self, name: &str) -> bool {
+		self.entries.iter().any(|f| f.name.eq_ignore_ascii_case(name))
+	}
+
+	pub fn get(&self, name: &str) -> Option<&str> {
+		self.get_field(name).map(|f| f.value.as_str())
+	}
+
+	pub fn get_bytes(&self, name: &str) -> Option<&[u8]> {
+		self.get_field(name).map(Field::value_bytes)
+	}
+
+	fn get_field(&self, name: &str) -> Option<&Field> {
+		self.entries
+			.iter()
+			.rev()
+			.find(|f| f.name.eq_ignore_ascii_case(name))
+	}
+
+	pub fn get_all<'a>(&'a self, name: &'a str) -> impl DoubleEndedIterator<Item=&'a str> {
+		self.get_all_fields(name).map(|f| f.value.as_str())
+	}
+
+	pub fn get_all_bytes<'a>(&'a self, name: &'a str) -> impl DoubleEndedIterator<Item=&'a [u8]> {
+		self.get_all_fields(name).map(Field::value_bytes)
+	}
+
+	fn get_all_fields<'a>(&'a self, name: &'a str) -> impl DoubleEndedIterator<Item=&'a Field> {
+		self.entries
+			.iter()
+			.filter(move |f| f.name.eq_ignore_ascii_case(name))
+	}
+
+	/** Replaces every existing field of that name, taking the place of the first one */
+	pub fn insert(&mut self, name: &str, value: &str) {
+		match self.entries.iter().position(|f| f.name.eq_ignore_ascii_case(name)) {
+			None => self.append(name, value),
+			Some(i) => {
+				self.entries[i] = Field::new(name, value);
+				let mut index = 0;
+				self.entries.retain(|f| {
+					index += 1;
+					index - 1 <= i || !f.name.eq_ignore_ascii_case(name)
+				});
+			}
+		}
+	}
+
+	pub fn append(&mut self, name: &str, value: &str) {
+		self.entries.push(Field::new(name, value));
+	}
+
+	/** For values as received, which may hold obs-text */
+	pub fn append_bytes(&mut self, name: &str, value: &[u8]) {
+		self.entries.push(match std::str::from_utf8(value) {
+			Ok(value) => Field::new(name, value),
+			Err(_) => Field {
+				raw_value: Some(value.to_vec()),
+				..Field::new(name, &String::from_utf8_lossy(value))
+			},
+		});
+	}
+
+	/// Appends `continuation` to the most recent field's value, a single SP between
+	/// non-empty parts; false if there's no field to continue.
+	pub(crate) fn continue_last(&mut self, continuation: &[u8]) -> bool {
+		if continuation.is_empty() {
+			return !self.entries.is_empty();
+		}
+		let Some(last) = self.entries.pop() else {
+			return false;
+		};
+
+		let mut value = last.value_bytes().to_vec();
+		if !value.is_empty() {
+			value.push(b' ');
+		}
+		value.extend_from_slice(continuation);
+		self.append_bytes(&last.name, &value);
+		true
+	}
+
+	/** Removes every field of that name, returns the last value */
+	pub fn remove(&mut self, name: &str) -> Option<String> {
+		let mut last = None;
+		self.entries.retain_mut(|f| {
+			if f.name.eq_ignore_ascii_case(name) {
+				last = Some(std::mem::take(&mut f.value));
+				false
+			} else {
+				true
+			}
+		});
+		last
+	}
+
+	pub fn iter(&self) -> Iter<'_> {
+		Iter(self.entries.iter())
+	}
+
+	pub fn iter_bytes(&self) -> impl Iterator<Item=(&str, &[u8])> {
+		self.entries.iter().map(|f| (f.name.as_str(), f.value_bytes()))
+	}
+}
+
+/** Name/value pairs in order, see [`HeaderMap::iter`] */
+pub struct Iter<'a>(std::slice::Iter<'a, Field>);
+
+impl<'a> Iterator for Iter<'a> {
+	type Item = (&'a str, &'a str);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		self.0.next().map(Field::as_pair)
+	}
+}
+
+impl<'a> IntoIterator for &'a HeaderMap {
+	type Item = (&'a str, &'a str);
+	type IntoIter = Iter<'a>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		Iter(self.entries.iter())
+	}
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for HeaderMap {
+	fn from_iter<T: IntoIterator<Item=(K, V)>>(iter: T) -> Self {
+		Self {
+			entries: iter.into_iter()
+				.map(|(k, v)| Field {
+					name: k.into(),
+					value: v.into(),
+					raw_value: None,
+				})
+				.collect(),
+		}
+	}
+}
+
+#[test]
+fn test_header_map() {
+	let mut headers = HeaderMap::new();
+	headers.append("Host", "unstd.pl");
+	headers.append("Accept", "text/html");
+	headers.append("accept", "text/plain");
+	headers.append("Dnt", "1");
+
+	assert_eq!(headers.get("host"), Some("unstd.pl"));
+	assert_eq!(headers.get("ACCEPT"), Some("text/plain"));
+	assert_eq!(headers.get_all("Accept").collect::<Vec<_>>(), vec!["text/html", "text/plain"]);
+	assert_eq!(headers.get("missing"), None);
+
+	headers.insert("ACCEPT", "*/*");
+	assert_eq!(
+		headers.iter().collect::<Vec<_>>(),
+		vec![("Host", "unstd.pl"), ("ACCEPT", "*/*"), ("Dnt", "1")]
+	);
+
+	assert_eq!(headers.remove("dnt"), Some("1".to_string()));
+	assert_eq!(headers.remove("dnt"), None);
+	assert_eq!(headers.len(), 2);
+
+	headers.append_bytes("X-Latin1", b"caf\xe9");
+	assert_eq!(headers.get("x-latin1"), Some("caf\u{FFFD}"));
+	assert_eq!(headers.get_bytes("x-latin1"), Some(&b"caf\xe9"[..]));
+	assert_eq!(headers.get_bytes("host"), Some(&b"unstd.pl"[..]));
+}
@@ -0,0 +1,227 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use crate::consts::MimeType;
+use crate::proto::parser::{is_tchar, is_token, ParseError};
+
+/** `type/subtype; param=value`, as in Content-Type, see RFC 9110 8.3.1 */
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MediaType {
+	type_: String,
+	subtype: String,
+	parameters: Vec<(String, String)>,
+}
+
+impl MediaType {
+	pub fn new(type_: &str, subtype: &str) -> Self {
+		Self {
+			type_: type_.to_ascii_lowercase(),
+			subtype: subtype.to_ascii_lowercase(),
+			parameters: vec![],
+		}
+	}
+
+	pub fn with_param(mut self, name: &str, value: &str) -> Self {
+		self.parameters.push((name.to_ascii_lowercase(), value.to_string()));
+		self
+	}
+
+	pub fn type_(&self) -> &str {
+		self.type_.as_str()
+	}
+	pub fn subtype(&self) -> &str {
+		self.subtype.as_str()
+	}
+	/** `type/subtype` without parameters */
+	pub fn essence(&self) -> String {
+		format!("{}/{}", self.type_, self.subtype)
+	}
+	pub fn params(&self) -> impl Iterator<Item=(&str, &str)> {
+		self.parameters.iter().map(|(n, v)| (n.as_str(), v.as_str()))
+	}
+	/** Parameter names are case-insensitive, values are returned unquoted */
+	pub fn param(&self, name: &str) -> Option<&str> {
+		self.parameters
+			.iter()
+			.find(|(n, _)| n.eq_ignore_ascii_case(name))
+			.map(|(_, v)| v.as_str())
+	}
+	pub fn charset(&self) -> Option<&str> {
+		self.param("charset")
+	}
+	pub fn boundary(&self) -> Option<&str> {
+		self.param("boundary")
+	}
+
+	pub fn is_multipart(&self) -> bool {
+		self.type_ == "multipart"
+	}
+
+	pub fn mime_type(&self) -> MimeType {
+		match (self.type_.as_str(), self.subtype.as_str()) {
+			("multipart", _) => MimeType::Multipart,
+			("text", "plain") => MimeType::TextPlain,
+			("text", "html") => MimeType::TextHtml,
+			("text" | "application", "json") => MimeType::TextJson,
+			("image", "png") => MimeType::ImagePng,
+			("image", "jpeg" | "jpg") => MimeType::ImageJpg,
+			("image", _) => MimeType::Image,
+			_ => MimeType::Unspecified,
+		}
+	}
+}
+
+fn skip_ows(s: &[u8], mut i: usize) -> usize {
+	while matches!(s.get(i), Some(b' ' | b'\t')) {
+		i += 1;
+	}
+	i
+}
+
+fn take_token(s: &[u8], mut i: usize) -> usize {
+	while s.get(i).copied().is_some_and(is_tchar) {
+		i += 1;
+	}
+	i
+}
+
+/** quoted-string starting at `s[i]`, returns the unescaped value and the index past it */
+fn take_quoted_string(s: &[u8], mut i: usize) -> Option<(Vec<u8>, usize)> {
+	let mut value = Vec::new();
+	i += 1;
+	loop {
+		match s.get(i).copied() {
+			None => return None,
+			Some(b'"') => return Some((value, i + 1)),
+			Some(b'\\') => {
+				value.push(*s.get(i + 1)?);
+				i += 2;
+			}
+			Some(c) => {
+				value.push(c);
+				i += 1;
+			}
+		}
+	}
+}
+
+/** `*( OWS ";" OWS [ name "=" value ] )`, shared with Content-Disposition */
+pub(crate) fn parse_parameters(s: &[u8]) -> Result<Vec<(String, String)>, ParseError> {
+	let mut parameters = Vec::new();
+	let mut i = skip_ows(s, 0);
+
+	while i < s.len() {
+		if s[i] != b';' {
+			return Err(ParseError::InvalidMediaType);
+		}
+		i = skip_ows(s, i + 1);
+
+		if i == s.len() || s[i] == b';' {
+			continue;
+		}
+
+		let name_end = take_token(s, i);
+		if name_end == i || s.get(name_end) != Some(&b'=') {
+			return Err(ParseError::InvalidMediaType);
+		}
+		let name = String::from_utf8_lossy(&s[i..name_end]).to_ascii_lowercase();
+		i = name_end + 1;
+
+		let value;
+		if s.get(i) == Some(&b'"') {
+			let (v, end) = take_quoted_string(s, i).ok_or(ParseError::InvalidMediaType)?;
+			value = String::from_utf8_lossy(&v).to_string();
+			i = end;
+		} else {
+			let end = take_token(s, i);
+			if end == i {
+				return Err(ParseError::InvalidMediaType);
+			}
+			value = String::from_utf8_lossy(&s[i..end]).to_string();
+			i = end;
+		}
+
+		parameters.push((name, value));
+		i = skip_ows(s, i);
+	}
+
+	Ok(parameters)
+}
+
+pub(crate) fn write_parameter(f: &mut Formatter<'_>, name: &str, value: &str) -> std::fmt::Result {
+	if is_token(value.as_bytes()) {
+		write!(f, "; {name}={value}")
+	} else {
+		write!(f, "; {name}=\"")?;
+		for c in value.chars() {
+			match c {
+				'"' | '\\' => write!(f, "\\{c}")?,
+				// no quoted-string can hold a control character, not even escaped
+				'\t' => write!(f, "{c}")?,
+				c if c.is_ascii_control() => write!(f, "%{:02X}", c as u8)?,
+				c => write!(f, "{c}")?,
+			}
+		}
+		write!(f, "\"")
+	}
+}
+
+impl FromStr for MediaType {
+	type Err = ParseError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let s = s.trim().as_bytes();
+
+		let type_end = take_token(s, 0);
+		if type_end == 0 || s.get(type_end) != Some(&b'/') {
+			return Err(ParseError::InvalidMediaType);
+		}
+		let subtype_end = take_token(s, type_end + 1);
+		if subtype_end == type_end + 1 {
+			return Err(ParseError::InvalidMediaType);
+		}
+
+		Ok(Self {
+			type_: String::from_utf8_lossy(&s[..type_end]).to_ascii_lowercase(),
+			subtype: String::from_utf8_lossy(&s[type_end + 1..subtype_end]).to_ascii_lowercase(),
+			parameters: parse_parameters(&s[subtype_end..])?,
+		})
+	}
+}
+
+impl Display for MediaType {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}/{}", self.type_, self.subtype)?;
+		for (name, value) in self.parameters.iter() {
+			write_parameter(f, name, value)?;
+		}
+		Ok(())
+	}
+}
+
+#[test]
+fn test_media_type() {
+	let media_type = MediaType::from_str("Text/HTML; Charset=\"utf-8\"").unwrap();
+	assert_eq!(media_type.essence(), "text/html");
+	assert_eq!(media_type.charset(), Some("utf-8"));
+	assert_eq!(media_type.mime_type(), MimeType::TextHtml);
+	assert_eq!(media_type.to_string(), "text/html; charset=utf-8");
+
+	let media_type = MediaType::from_str(
+		"multipart/form-data ; boundary=\"----with \\\"quotes\\\" and ;\"").unwrap();
+	assert!(media_type.is_multipart());
+	assert_eq!(media_type.boundary(), Some("----with \"quotes\" and ;"));
+	assert_eq!(
+		media_type.to_string(),
+		"multipart/form-data; boundary=\"----with \\\"quotes\\\" and ;\""
+	);
+	assert_eq!(MediaType::from_str(&media_type.to_string()), Ok(media_type));
+	assert_eq!(
+		MediaType::new("text", "plain").with_param("name", "a\r\nb").to_string(),
+		"text/plain; name=\"a%0D%0Ab\""
+	);
+
+	assert_eq!(MediaType::from_str("text"), Err(ParseError::InvalidMediaType));
+	assert_eq!(MediaType::from_str("text/"), Err(ParseError::InvalidMediaType));
+	assert_eq!(MediaType::from_str("text/plain; charset"), Err(ParseError::InvalidMediaType));
+	assert_eq!(MediaType::from_str("text/plain; a=\"open"), Err(ParseError::InvalidMediaType));
+}
@@ -1,39 +1,51 @@
 mod collector;
 mod builder;
+mod serialize;
 
 pub use collector::*;
 pub use builder::*;
+pub(crate) use serialize::check_field;
+use std::fmt::{Debug, Formatter};
+use std::io::Read;
 use crate::consts::Version;
+use crate::header_map::HeaderMap;
+use crate::media_type::MediaType;
+
+/** Body data of unknown length, sent with chunked framing */
+pub struct BodyReader(pub(crate) Box<dyn Read>);
+
+impl Debug for BodyReader {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		f.write_str("BodyReader")
+	}
+}
 
 #[derive(Debug)]
 pub struct Message {
 	version: Version,
-	headers: Vec<(String, String)>,
+	headers: HeaderMap,
 	body: Vec<u8>,
+	body_reader: Option<BodyReader>,
+	trailers: HeaderMap,
 }
 
 impl Message {
 	pub fn version(&self) -> Version {
 		self.version
 	}
-}
-
-impl Message {
-	pub fn into_bytes(self) -> Vec<u8> {
-		let mut ret = Vec::new();
-
-		ret.extend_from_slice(
-			self.headers
-				.iter()
-				.map(|(k, v)| format!("{}: {}\r\n", k, v))
-				.collect::<String>()
-				.as_bytes()
-		);
-
-		ret.extend_from_slice(b"\r\n");
-
-		ret.extend_from_slice(self.body.as_slice());
-
-		ret
+	pub fn headers(&self) -> &HeaderMap {
+		&self.headers
+	}
+	/** `None` when the header is missing or malformed */
+	pub fn content_type(&self) -> Option<MediaType> {
+		self.headers.get("content-type")?.parse().ok()
+	}
+	/** Doesn't include data still to be read from `BodyReader` */
+	pub fn body(&self) -> &[u8] {
+		self.body.as_slice()
+	}
+	/** Fields sent after a chunked body, kept apart from `headers` */
+	pub fn trailers(&self) -> &HeaderMap {
+		&self.trailers
 	}
 }
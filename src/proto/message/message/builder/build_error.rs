@@ -0,0 +1,49 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum BuildError {
+	InvalidContentLength,
+	ContentLengthMismatch {
+		declared: usize,
+		actual: usize,
+	},
+	UnsupportedTransferEncoding,
+	ConflictingFraming,
+	BodyNotAllowed,
+	/** Content-Length or Transfer-Encoding on a 1xx or 204 response, RFC 9110 8.6 */
+	FramingNotAllowed,
+	/** anything but HTAB, SP, VCHAR and obs-text in a reason phrase */
+	InvalidReasonPhrase,
+	/** CONNECT without a host to connect to */
+	MissingAuthority,
+	/** SP, a control character or non-ASCII in the request target, e.g. from a hand-made `Url` */
+	InvalidRequestTarget,
+	/** a header or trailer name that isn't a token */
+	InvalidFieldName,
+	/** CR, LF or NUL in a header or trailer value, i.e. an injected line */
+	InvalidFieldValue,
+	Io(std::io::ErrorKind),
+}
+
+impl Display for BuildError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		use BuildError::*;
+		match self {
+			InvalidContentLength => f.write_str("invalid Content-Length"),
+			ContentLengthMismatch { declared, actual } =>
+				write!(f, "Content-Length of {declared} for a body of {actual} bytes"),
+			UnsupportedTransferEncoding => f.write_str("unsupported Transfer-Encoding"),
+			ConflictingFraming => f.write_str("conflicting Content-Length and Transfer-Encoding"),
+			BodyNotAllowed => f.write_str("a body where none is allowed"),
+			FramingNotAllowed => f.write_str("Content-Length or Transfer-Encoding where none is allowed"),
+			InvalidReasonPhrase => f.write_str("invalid reason phrase"),
+			MissingAuthority => f.write_str("CONNECT without an authority"),
+			InvalidRequestTarget => f.write_str("invalid request target"),
+			InvalidFieldName => f.write_str("invalid field name"),
+			InvalidFieldValue => f.write_str("invalid field value"),
+			Io(kind) => write!(f, "reading the body failed: {kind}"),
+		}
+	}
+}
+
+impl std::error::Error for BuildError {}
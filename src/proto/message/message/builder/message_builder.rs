@@ -1,24 +1,54 @@
+use std::io::{Cursor, Read};
 use crate::consts::Version;
-use crate::proto::message::Message;
+use crate::header_map::HeaderMap;
+use crate::proto::message::{BodyReader, Message};
+use crate::multipart::MultipartBody;
 
+#[derive(Default)]
 pub struct MessageBuilder {
-	headers: Vec<(String, String)>,
+	headers: HeaderMap,
 	body: Vec<u8>,
-}
-
-impl Default for MessageBuilder {
-	fn default() -> Self {
-		Self {
-			headers: vec![],
-			body: vec![],
-		}
-	}
+	body_reader: Option<Box<dyn Read>>,
 }
 
 impl MessageBuilder {
 	pub fn push_header(&mut self, field_name: &str, field_value: &str)
 	-> &mut Self {
-		self.headers.push((field_name.to_string(), field_value.to_string()));
+		self.headers.append(field_name, field_value);
+		self
+	}
+	/** Replaces any header of the same name */
+	pub fn set_header(&mut self, field_name: &str, field_value: &str)
+	-> &mut Self {
+		self.headers.insert(field_name, field_value);
+		self
+	}
+	pub fn set_body(&mut self, body: &[u8]) -> &mut Self {
+		self.body = body.to_vec();
+		self.body_reader = None;
+		self
+	}
+	pub fn append_body(&mut self, body: &[u8]) -> &mut Self {
+		match self.body_reader.take() {
+			None => self.body.extend_from_slice(body),
+			Some(reader) => {
+				self.body_reader = Some(Box::new(reader.chain(Cursor::new(body.to_vec()))));
+			}
+		}
+		self
+	}
+	/** Sets both the body and the Content-Type carrying its boundary */
+	pub fn set_multipart(&mut self, multipart: MultipartBody) -> &mut Self {
+		let (content_type, body) = multipart.into_parts();
+		self.headers.insert("Content-Type", &content_type.to_string());
+		self.body = body;
+		self.body_reader = None;
+		self
+	}
+	/** The body's length is unknown until `reader` is exhausted, so it's sent chunked */
+	pub fn body_from_reader<R: Read + 'static>(&mut self, reader: R) -> &mut Self {
+		self.body.clear();
+		self.body_reader = Some(Box::new(reader));
 		self
 	}
 }
@@ -29,6 +59,101 @@ impl MessageBuilder {
 			version: http_version,
 			headers: self.headers,
 			body: self.body,
+			body_reader: self.body_reader.map(BodyReader),
+			trailers: HeaderMap::new(),
 		}
 	}
 }
+
+#[cfg(test)]
+fn serialized(builder: MessageBuilder, body_rule: crate::proto::message::BodyRule)
+	-> Result<Vec<u8>, crate::proto::message::BuildError> {
+	builder.into_message(Version::HTTP_1_1).into_bytes(body_rule)
+}
+
+#[test]
+fn test_automatic_framing() {
+	use crate::proto::message::{BodyRule, BuildError};
+
+	let mut builder = MessageBuilder::default();
+	builder.set_body(b"hello").append_body(b" world");
+	assert_eq!(
+		serialized(builder, BodyRule::UntilCloseIfUnframed),
+		Ok(b"Content-Length: 11\r\n\r\nhello world".to_vec())
+	);
+
+	let builder = MessageBuilder::default();
+	assert_eq!(serialized(builder, BodyRule::EmptyIfUnframed), Ok(b"\r\n".to_vec()));
+
+	let mut builder = MessageBuilder::default();
+	builder.body_from_reader(Cursor::new(b"hello".to_vec())).append_body(b" world");
+	assert_eq!(
+		serialized(builder, BodyRule::UntilCloseIfUnframed),
+		Ok(b"Transfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n".to_vec())
+	);
+
+	let mut builder = MessageBuilder::default();
+	builder.push_header("content-length", "4").set_body(b"hello");
+	assert_eq!(
+		serialized(builder, BodyRule::UntilCloseIfUnframed),
+		Err(BuildError::ContentLengthMismatch { declared: 4, actual: 5 })
+	);
+
+	let mut builder = MessageBuilder::default();
+	builder.push_header("content-length", "5").body_from_reader(Cursor::new(b"hello"));
+	assert_eq!(
+		serialized(builder, BodyRule::EmptyIfUnframed),
+		Ok(b"content-length: 5\r\n\r\nhello".to_vec())
+	);
+
+	let mut builder = MessageBuilder::default();
+	builder.set_body(b"hello");
+	assert_eq!(serialized(builder, BodyRule::NoBody), Err(BuildError::BodyNotAllowed));
+
+	// HTTP/1.0 has no chunked framing, the reader is read up front
+	let mut builder = MessageBuilder::default();
+	builder.body_from_reader(Cursor::new(b"hello".to_vec()));
+	assert_eq!(
+		builder.into_message(Version::HTTP_1_0).into_bytes(BodyRule::UntilCloseIfUnframed),
+		Ok(b"Content-Length: 5\r\n\r\nhello".to_vec())
+	);
+	let mut builder = MessageBuilder::default();
+	builder.body_from_reader(Cursor::new(b"hello".to_vec()));
+	assert_eq!(
+		builder.into_message(Version::HTTP_1_0).into_bytes(BodyRule::EmptyIfUnframed),
+		Ok(b"Content-Length: 5\r\n\r\nhello".to_vec())
+	);
+	let mut builder = MessageBuilder::default();
+	builder.push_header("Transfer-Encoding", "chunked");
+	assert_eq!(
+		builder.into_message(Version::HTTP_1_0).into_bytes(BodyRule::UntilCloseIfUnframed),
+		Err(BuildError::UnsupportedTransferEncoding)
+	);
+}
+
+#[test]
+fn test_field_injection() {
+	use crate::proto::message::{BodyRule, BuildError};
+
+	let mut builder = MessageBuilder::default();
+	builder.push_header("X", "a\r\nSet-Cookie: evil=1");
+	assert_eq!(serialized(builder, BodyRule::EmptyIfUnframed), Err(BuildError::InvalidFieldValue));
+
+	let mut builder = MessageBuilder::default();
+	builder.push_header("X", "a\0b");
+	assert_eq!(serialized(builder, BodyRule::EmptyIfUnframed), Err(BuildError::InvalidFieldValue));
+
+	let mut builder = MessageBuilder::default();
+	builder.push_header("Set-Cookie: evil=1\r\nX", "a");
+	assert_eq!(serialized(builder, BodyRule::EmptyIfUnframed), Err(BuildError::InvalidFieldName));
+
+	// obs-text and tabs are fine in a value
+	let mut builder = MessageBuilder::default();
+	builder.push_header("X", "caf\u{e9}\tok");
+	assert!(serialized(builder, BodyRule::EmptyIfUnframed).is_ok());
+
+	assert_eq!(
+		BuildError::ContentLengthMismatch { declared: 4, actual: 5 }.to_string(),
+		"Content-Length of 4 for a body of 5 bytes"
+	);
+}
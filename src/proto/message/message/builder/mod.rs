@@ -1,2 +1,4 @@
 mod message_builder;
+mod build_error;
 pub use message_builder::*;
+pub use build_error::BuildError;
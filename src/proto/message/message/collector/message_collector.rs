@@ -1,44 +1,125 @@
 mod into_message;
+mod framing;
 
+pub use framing::{BodyRule, TransferStrategy};
+
+use crate::config::{CollectorLimits, ParserConfig};
+use crate::consts::Version;
+use crate::header_map::HeaderMap;
 use crate::proto::buffer_reader::{DelayedConsumeResult, DelayedStateBuffer};
 use crate::proto::parser;
-use crate::proto::parser::{HeaderLineParseResult, ParseError};
+use crate::proto::parser::{CollectError, HeaderLineParseResult, ParseError};
 
 
-pub type CollectResult = Option<Result<(), ParseError>>;
+pub type CollectResult = Option<Result<(), CollectError>>;
 
 #[derive(Copy, Clone, Default)]
-enum CollectPhase {
+pub enum CollectPhase {
 	#[default]
 	FirstLine,
 	MainHeaders,
 	MainBody,
+	ContentLengthBody(usize),
+	ChunkSize,
+	ChunkData(usize),
+	ChunkDataEnd,
+	Trailers,
+	UntilCloseBody,
 }
 
 #[derive(Copy, Clone)]
 pub enum CollectorState {
 	Incomplete(CollectPhase),
-	Finished(Result<(), ParseError>),
+	Finished(Result<(), CollectError>),
 }
 
 pub struct MessageCollector {
 	collector_state: CollectorState,
+	body_rule: BodyRule,
 
-	collected_headers: Vec<(Vec<u8>, Vec<u8>)>,
+	collected_headers: HeaderMap,
 	collected_body: Vec<u8>,
+	collected_trailers: HeaderMap,
 
 	master_buffer_reader: DelayedStateBuffer,
+	/** stream position of the first byte of the caller's buffer */
+	stream_offset: usize,
+
+	limits: CollectorLimits,
+	config: ParserConfig,
+	/** from the first line, framing rules differ for HTTP/1.0 */
+	version: Version,
+	/** faulty framing let through by the config, see [`Self::must_close`] */
+	must_close: bool,
+	message_start: usize,
+	header_count: usize,
+	body_size: usize,
+}
+
+impl Default for MessageCollector {
+	fn default() -> Self {
+		Self::new()
+	}
 }
 
 impl MessageCollector {
 	pub fn new() -> Self {
+		Self::starting_at(0, CollectorLimits::default(), ParserConfig::default())
+	}
+
+	pub fn with_limits(limits: CollectorLimits) -> Self {
+		Self::starting_at(0, limits, ParserConfig::default())
+	}
+
+	pub fn limits(&self) -> CollectorLimits {
+		self.limits
+	}
+
+	pub fn config(&self) -> ParserConfig {
+		self.config
+	}
+
+	/** Only to be called before the first `advance` */
+	pub fn set_config(&mut self, config: ParserConfig) {
+		self.config = config;
+	}
+
+	/// The framing was faulty, but let through by the config; where the next
+	/// message would start can't be trusted, RFC 9112 6.1 has the connection
+	/// closed after this one.
+	pub fn must_close(&self) -> bool {
+		self.must_close
+	}
+
+	/// A collector for the message following this one on the same stream, to be
+	/// advanced over the same buffer; nothing consumed so far is scanned again.
+	pub fn next_message(&self) -> Self {
+		Self {
+			master_buffer_reader: self.master_buffer_reader.following(),
+			message_start: self.stream_position(),
+			..Self::starting_at(self.stream_offset, self.limits, self.config)
+		}
+	}
+
+	fn starting_at(stream_offset: usize, limits: CollectorLimits, config: ParserConfig) -> Self {
 		Self {
 			collector_state: CollectorState::Incomplete(Default::default()),
+			body_rule: BodyRule::EmptyIfUnframed,
 
-			collected_headers: vec![],
+			collected_headers: HeaderMap::new(),
 			collected_body: vec![],
+			collected_trailers: HeaderMap::new(),
 
 			master_buffer_reader: DelayedStateBuffer::new(),
+			stream_offset,
+
+			limits,
+			config,
+			version: Version::HTTP_1_1,
+			must_close: false,
+			message_start: stream_offset,
+			header_count: 0,
+			body_size: 0,
 		}
 	}
 }
@@ -48,7 +129,7 @@ pub enum MessageCollectorAdvance {
 	Finished {
 		remaining_bytes: usize
 	},
-	Error(ParseError),
+	Error(CollectError),
 }
 
 enum AdvanceSingleResult {
@@ -56,42 +137,48 @@ enum AdvanceSingleResult {
 	ChangePhase(CollectPhase),
 	NotEnoughBytes,
 	Finished,
-	Error(ParseError),
+	Error(CollectError),
 }
 
 impl MessageCollector {
-	pub fn is_finished(&self) -> bool {
-		use CollectorState::*;
-		match &self.collector_state {
-			Incomplete(_) => false,
-			Finished(_) => true
-		}
-	}
-
 	pub fn advance<F>(&mut self, buffer: &[u8], mut on_first_line: F)
 					  -> MessageCollectorAdvance
 	where
-		F: FnMut(&[u8]) -> Result<(), ParseError>,
+		F: FnMut(&[u8]) -> Result<(Version, BodyRule), ParseError>,
 	{
 		use AdvanceSingleResult::*;
 
+		if let CollectorState::Finished(result) = self.collector_state {
+			return match result {
+				Ok(()) => MessageCollectorAdvance::Finished {
+					remaining_bytes: buffer.len() - self.master_buffer_reader.consumed(),
+				},
+				Err(e) => MessageCollectorAdvance::Error(e),
+			};
+		}
+
 		if let CollectorState::Incomplete(CollectPhase::FirstLine) = self.collector_state {
-			match self.master_buffer_reader.take_line(buffer) {
-				DelayedConsumeResult::NotEnoughBytes =>
-					return MessageCollectorAdvance::NeedMoreBytes,
-				DelayedConsumeResult::Finished { slice, .. } => {
-					match on_first_line(slice) {
-						Ok(()) => {
-							self.collector_state =
-								CollectorState::Incomplete(
-									CollectPhase::MainHeaders);
-						}
-						Err(e) => {
-							return MessageCollectorAdvance::Error(e)
-						}
-					}
-				}
+			let result = match self.take_line(
+				buffer, self.limits.max_first_line, ParseError::FirstLineTooLong) {
+				Ok(None) => return MessageCollectorAdvance::NeedMoreBytes,
+				Ok(Some((base_index, slice))) => on_first_line(slice)
+					.map_err(|kind| self.error_at(kind, base_index)),
+				Err(e) => Err(e),
 			};
+
+			match result {
+				Ok((version, body_rule)) => {
+					self.version = version;
+					self.body_rule = body_rule;
+					self.collector_state =
+						CollectorState::Incomplete(
+							CollectPhase::MainHeaders);
+				}
+				Err(e) => {
+					self.collector_state = CollectorState::Finished(Err(e));
+					return MessageCollectorAdvance::Error(e);
+				}
+			}
 		}
 
 		loop {
@@ -118,6 +205,170 @@ impl MessageCollector {
 		}
 	}
 
+	/** Body bytes collected so far; later ones keep accumulating from empty */
+	pub fn take_body(&mut self) -> Vec<u8> {
+		std::mem::take(&mut self.collected_body)
+	}
+
+	/// Drops everything consumed so far from the front of `buffer`, so that a long
+	/// body doesn't have to stay in memory twice.
+	pub fn compact(&mut self, buffer: &mut Vec<u8>) {
+		let consumed = self.master_buffer_reader.forget_consumed();
+		self.stream_offset += consumed;
+		buffer.drain(..consumed);
+	}
+
+	/** Stream position of the first byte of this message */
+	pub fn message_start(&self) -> usize {
+		self.message_start
+	}
+
+	/** Stream position just past the bytes consumed so far */
+	pub fn stream_position(&self) -> usize {
+		self.stream_offset + self.master_buffer_reader.consumed()
+	}
+
+	/// The peer closed the connection; only a body delimited by the close
+	/// itself can be completed by this.
+	pub fn signal_connection_close(&mut self, buffer: &[u8]) -> MessageCollectorAdvance {
+		let advance = self.advance(buffer, |_| Err(ParseError::IncompleteMessage));
+
+		if let MessageCollectorAdvance::NeedMoreBytes = advance {
+			self.collector_state = match self.collector_state {
+				CollectorState::Incomplete(CollectPhase::UntilCloseBody) =>
+					CollectorState::Finished(Ok(())),
+				_ => CollectorState::Finished(Err(
+					self.error_at(ParseError::IncompleteMessage, buffer.len()))),
+			};
+			return self.advance(buffer, |_| unreachable!());
+		}
+
+		advance
+	}
+
+	/** `index` is relative to the caller's buffer */
+	fn error_at(&self, kind: ParseError, index: usize) -> CollectError {
+		CollectError::new(kind, self.stream_offset + index)
+	}
+
+	/** A complete line with its position in `buffer`, CRLF stripped; `too_long` past `max_length` */
+	fn take_line<'a>(&mut self, buffer: &'a [u8], max_length: usize, too_long: ParseError)
+					 -> Result<Option<(usize, &'a [u8])>, CollectError> {
+		let line = self.master_buffer_reader.take_line(buffer);
+		// without a line end, all that's pending belongs to the line
+		self.check_preamble_size(match line {
+			DelayedConsumeResult::NotEnoughBytes => buffer.len(),
+			DelayedConsumeResult::Finished { base_index, consumed, .. } => base_index + consumed,
+		})?;
+
+		match line {
+			DelayedConsumeResult::NotEnoughBytes => {
+				let pending = buffer.len() - self.master_buffer_reader.consumed();
+				if pending > max_length.saturating_add(2) {
+					return Err(self.error_at(too_long, self.master_buffer_reader.consumed()));
+				}
+				Ok(None)
+			}
+			DelayedConsumeResult::Finished { base_index, consumed, slice } => {
+				if slice.len() > max_length {
+					return Err(self.error_at(too_long, base_index));
+				}
+				if consumed == slice.len() + 1 && !self.config.allow_bare_lf {
+					return Err(self.error_at(ParseError::BareLF, base_index + slice.len()));
+				}
+				if let Some(i) = slice.iter().position(|c| *c == b'\r') {
+					return Err(self.error_at(ParseError::BareCR, base_index + i));
+				}
+				Ok(Some((base_index, slice)))
+			}
+		}
+	}
+
+	/// Everything before the body up to `end` in the caller's buffer, i.e. the
+	/// line just taken or what's buffered of an incomplete one; never the body.
+	fn check_preamble_size(&self, end: usize) -> Result<(), CollectError> {
+		let in_preamble = matches!(self.collector_state,
+			CollectorState::Incomplete(CollectPhase::FirstLine | CollectPhase::MainHeaders));
+		let preamble = self.stream_offset + end - self.message_start;
+
+		if in_preamble && preamble > self.limits.max_preamble {
+			return Err(CollectError::new(
+				ParseError::PreambleTooLarge,
+				self.message_start.saturating_add(self.limits.max_preamble)));
+		}
+		Ok(())
+	}
+
+	fn count_body_bytes(&mut self, n: usize, index: usize) -> Result<(), CollectError> {
+		// `n` may be a chunk size straight from the peer, don't let it overflow
+		if n > self.limits.max_body - self.body_size {
+			return Err(self.error_at(ParseError::BodyTooLarge, index));
+		}
+		self.body_size += n;
+		Ok(())
+	}
+
+	/** obs-fold, RFC 9112 5.2: joined onto the previous value with a single SP if allowed */
+	fn continue_field_line(&mut self, line: &[u8], base_index: usize, trailers: bool)
+						   -> Result<(), CollectError> {
+		if !self.config.allow_obs_fold {
+			return Err(self.error_at(ParseError::ObsFold, base_index));
+		}
+
+		if let Some(i) = line.iter().position(|c| !parser::valid_field_value_byte(*c)) {
+			return Err(self.error_at(ParseError::InvalidFieldValue, base_index + i));
+		}
+
+		let into = match trailers {
+			true => &mut self.collected_trailers,
+			false => &mut self.collected_headers,
+		};
+		// (!) nothing to continue right after the first line
+		match into.continue_last(line.trim_ascii()) {
+			true => Ok(()),
+			false => Err(self.error_at(ParseError::ObsFold, base_index)),
+		}
+	}
+
+	/** Appends one header or trailer line; `Some(false)` on the empty line ending the section */
+	fn take_field_line(&mut self, buffer: &[u8], trailers: bool)
+					   -> Result<Option<bool>, CollectError> {
+		let Some((base_index, slice)) = self.take_line(
+			buffer, self.limits.max_header_line, ParseError::HeaderLineTooLong)? else {
+			return Ok(None);
+		};
+
+		if let [b' ' | b'\t', ..] = slice {
+			self.continue_field_line(slice, base_index, trailers)?;
+			return Ok(Some(true));
+		}
+
+		if !slice.is_empty() {
+			self.header_count += 1;
+			if self.header_count > self.limits.max_header_count {
+				return Err(self.error_at(ParseError::TooManyHeaders, base_index));
+			}
+		}
+
+		match parser::parse_header_line(slice, &self.config) {
+			HeaderLineParseResult::Empty => Ok(Some(false)),
+			HeaderLineParseResult::Err { kind, index } =>
+				Err(self.error_at(kind, base_index + index)),
+			HeaderLineParseResult::Ok {
+				field_name,
+				field_value
+			} => {
+				let into = match trailers {
+					true => &mut self.collected_trailers,
+					false => &mut self.collected_headers,
+				};
+				// field names are made of tchar only, ASCII
+				into.append_bytes(&String::from_utf8_lossy(field_name), field_value);
+				Ok(Some(true))
+			}
+		}
+	}
+
 	fn advance_single(&mut self, buffer: &[u8]) -> AdvanceSingleResult {
 		use CollectPhase::*;
 		use DelayedConsumeResult::*;
@@ -128,38 +379,367 @@ impl MessageCollector {
 			CollectorState::Incomplete(phase) => match phase {
 				FirstLine => unreachable!(),
 				MainHeaders => {
-					match self.master_buffer_reader.take_line(&buffer) {
+					match self.take_field_line(buffer, false) {
+						Ok(None) => ADV::NotEnoughBytes,
+						Ok(Some(true)) => ADV::CanContinue,
+						Ok(Some(false)) => {
+							// trailers get a count of their own
+							self.header_count = 0;
+							ADV::ChangePhase(MainBody)
+						}
+						Err(e) => ADV::Error(e),
+					}
+				}
+				MainBody => {
+					match self.transfer_strategy() {
+						Ok(TransferStrategy::None) => ADV::Finished,
+						Ok(TransferStrategy::ContentLength(length)) if length > self.limits.max_body =>
+							ADV::Error(self.error_at(
+								ParseError::BodyTooLarge, self.master_buffer_reader.consumed())),
+						Ok(TransferStrategy::ContentLength(length)) =>
+							ADV::ChangePhase(ContentLengthBody(length)),
+						Ok(TransferStrategy::Chunked) => ADV::ChangePhase(ChunkSize),
+						Ok(TransferStrategy::UntilClose) => ADV::ChangePhase(UntilCloseBody),
+						// the header section as a whole is at fault, point past its end
+						Err(kind) => ADV::Error(
+							self.error_at(kind, self.master_buffer_reader.consumed())),
+					}
+				}
+				ContentLengthBody(remaining) => {
+					match self.master_buffer_reader.take_up_to(buffer, remaining) {
 						NotEnoughBytes => ADV::NotEnoughBytes,
 						Finished { slice, .. } => {
-							match parser::parse_header_line(slice) {
-								HeaderLineParseResult::Empty => {
-									ADV::ChangePhase(MainBody)
-								}
-								HeaderLineParseResult::Err(e) => {
-									dbg!(e, &self.master_buffer_reader);
-									dbg!(String::from_utf8_lossy(slice));
-									ADV::Error(e)
-								}
-								HeaderLineParseResult::Ok {
-									field_name,
-									field_value
-								} => {
-									self.collected_headers
-										.push((
-											field_name.to_owned(),
-											field_value.to_owned(),
-										));
-									ADV::CanContinue
-								}
+							self.collected_body.extend_from_slice(slice);
+							match remaining - slice.len() {
+								0 => ADV::Finished,
+								remaining => ADV::ChangePhase(ContentLengthBody(remaining)),
 							}
 						}
 					}
 				}
-				MainBody => {
-					// dbg!(&self.collected_headers);
-					ADV::Finished
+				ChunkSize => {
+					match self.take_line(
+						buffer, self.limits.max_chunk_line, ParseError::ChunkLineTooLong) {
+						Ok(None) => ADV::NotEnoughBytes,
+						Ok(Some((base_index, slice))) => {
+							match parser::parse_chunk_size_line(slice) {
+								Ok(0) => ADV::ChangePhase(Trailers),
+								Ok(size) => match self.count_body_bytes(size, base_index) {
+									Ok(()) => ADV::ChangePhase(ChunkData(size)),
+									Err(e) => ADV::Error(e),
+								},
+								Err(kind) => ADV::Error(self.error_at(kind, base_index)),
+							}
+						}
+						Err(e) => ADV::Error(e),
+					}
+				}
+				ChunkData(remaining) => {
+					match self.master_buffer_reader.take_up_to(buffer, remaining) {
+						NotEnoughBytes => ADV::NotEnoughBytes,
+						Finished { slice, .. } => {
+							self.collected_body.extend_from_slice(slice);
+							match remaining - slice.len() {
+								0 => ADV::ChangePhase(ChunkDataEnd),
+								remaining => ADV::ChangePhase(ChunkData(remaining)),
+							}
+						}
+					}
+				}
+				ChunkDataEnd => {
+					let next = buffer.get(self.master_buffer_reader.consumed());
+					let length = match next {
+						Some(b'\n') if self.config.allow_bare_lf => 1,
+						_ => 2,
+					};
+					match self.master_buffer_reader.take_exact(buffer, length) {
+						NotEnoughBytes => ADV::NotEnoughBytes,
+						Finished { slice: b"\r\n" | b"\n", .. } => ADV::ChangePhase(ChunkSize),
+						Finished { base_index, .. } => ADV::Error(
+							self.error_at(ParseError::InvalidChunkDelimiter, base_index)),
+					}
+				}
+				UntilCloseBody => {
+					if let Finished { slice, base_index, .. } = self.master_buffer_reader.take_all(buffer) {
+						if let Err(e) = self.count_body_bytes(slice.len(), base_index) {
+							return ADV::Error(e);
+						}
+						self.collected_body.extend_from_slice(slice);
+					}
+					ADV::NotEnoughBytes
+				}
+				Trailers => {
+					match self.take_field_line(buffer, true) {
+						Ok(None) => ADV::NotEnoughBytes,
+						Ok(Some(true)) => ADV::CanContinue,
+						Ok(Some(false)) => ADV::Finished,
+						Err(e) => ADV::Error(e),
+					}
 				}
 			}
 		}
 	}
 }
+
+#[cfg(test)]
+use crate::consts::StatusCode;
+
+#[cfg(test)]
+fn collect_in_parts(parts: &[&[u8]]) -> (MessageCollector, Option<CollectError>) {
+	let mut collector = MessageCollector::new();
+	let mut buffer = Vec::new();
+	for part in parts {
+		buffer.extend_from_slice(part);
+		match collector.advance(&buffer, |_| Ok((Version::HTTP_1_1, BodyRule::EmptyIfUnframed))) {
+			MessageCollectorAdvance::NeedMoreBytes => continue,
+			MessageCollectorAdvance::Finished { .. } => break,
+			MessageCollectorAdvance::Error(e) => return (collector, Some(e)),
+		}
+	}
+	(collector, None)
+}
+
+#[test]
+fn test_content_length_body() {
+	let (collector, err) = collect_in_parts(&[
+		b"POST / HTTP/1.1\r\ncontent-length: 11\r\n",
+		b"\r\nhello",
+		b" wor",
+		b"ld and some more",
+	]);
+	assert_eq!(err, None);
+	assert!(matches!(collector.collector_state, CollectorState::Finished(Ok(()))));
+	assert_eq!(collector.collected_body, b"hello world");
+
+	let (collector, err) = collect_in_parts(&[
+		b"POST / HTTP/1.1\r\nContent-Length: 3\r\nContent-Length: 3\r\n\r\nabc",
+	]);
+	assert_eq!(err, None);
+	assert_eq!(collector.collected_body, b"abc");
+}
+
+#[test]
+fn test_content_length_errors() {
+	let (_, err) = collect_in_parts(&[
+		b"POST / HTTP/1.1\r\ncontent-length: -1\r\n\r\n",
+	]);
+	assert_eq!(err.map(|e| e.kind()), Some(ParseError::InvalidContentLength));
+
+	let (_, err) = collect_in_parts(&[
+		b"POST / HTTP/1.1\r\ncontent-length: 12a\r\n\r\n",
+	]);
+	assert_eq!(err.map(|e| e.kind()), Some(ParseError::InvalidContentLength));
+
+	let (_, err) = collect_in_parts(&[
+		b"POST / HTTP/1.1\r\ncontent-length: 3\r\ncontent-length: 4\r\n\r\n",
+	]);
+	assert_eq!(err.map(|e| e.kind()), Some(ParseError::ConflictingContentLength));
+
+	let (_, err) = collect_in_parts(&[
+		b"POST / HTTP/1.1\r\ncontent-length: 3, 4\r\n\r\n",
+	]);
+	assert_eq!(err.map(|e| e.kind()), Some(ParseError::ConflictingContentLength));
+}
+
+#[test]
+fn test_chunked_body() {
+	let (collector, err) = collect_in_parts(&[
+		b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n",
+		b"5\r\nhello\r\n",
+		b"6;ext=\"x\"\r\n wor",
+		b"ld\r\n0\r\n",
+		b"Expires: never\r\n\r\n",
+	]);
+	assert_eq!(err, None);
+	assert!(matches!(collector.collector_state, CollectorState::Finished(Ok(()))));
+	assert_eq!(collector.collected_body, b"hello world");
+	assert_eq!(collector.collected_headers.len(), 1);
+	assert_eq!(collector.collected_trailers.get("expires"), Some("never"));
+	assert_eq!(collector.collected_trailers.len(), 1);
+}
+
+#[test]
+fn test_chunked_errors() {
+	let (_, err) = collect_in_parts(&[
+		b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhelloXX",
+	]);
+	assert_eq!(err.map(|e| e.kind()), Some(ParseError::InvalidChunkDelimiter));
+
+	let (_, err) = collect_in_parts(&[
+		b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nzz\r\n",
+	]);
+	assert_eq!(err.map(|e| e.kind()), Some(ParseError::InvalidChunkSize));
+
+	let (_, err) = collect_in_parts(&[
+		b"HTTP/1.1 200 OK\r\nTransfer-Encoding: gzip, chunked\r\n\r\n",
+	]);
+	assert_eq!(err.map(|e| e.kind()), Some(ParseError::UnsupportedTransferEncoding));
+}
+
+#[test]
+fn test_until_close_body() {
+	let mut collector = MessageCollector::new();
+	let mut buffer = Vec::from(&b"HTTP/1.0 200 OK\r\nServer: old\r\n\r\nhello"[..]);
+	assert!(matches!(
+		collector.advance(&buffer, |_| Ok((Version::HTTP_1_1, BodyRule::UntilCloseIfUnframed))),
+		MessageCollectorAdvance::NeedMoreBytes
+	));
+	buffer.extend_from_slice(b" world");
+	assert!(matches!(
+		collector.advance(&buffer, |_| unreachable!()),
+		MessageCollectorAdvance::NeedMoreBytes
+	));
+	assert!(matches!(
+		collector.signal_connection_close(&buffer),
+		MessageCollectorAdvance::Finished { remaining_bytes: 0 }
+	));
+	assert_eq!(collector.collected_body, b"hello world");
+
+	let (collector, err) = collect_in_parts(&[
+		b"GET / HTTP/1.1\r\nHost: x\r\n\r\nGET /next HTTP/1.1\r\n",
+	]);
+	assert_eq!(err, None);
+	assert!(matches!(collector.collector_state, CollectorState::Finished(Ok(()))));
+	assert!(collector.collected_body.is_empty());
+
+	let mut collector = MessageCollector::new();
+	let buffer = b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nhello";
+	collector.advance(buffer, |_| Ok((Version::HTTP_1_1, BodyRule::UntilCloseIfUnframed)));
+	assert!(matches!(
+		collector.signal_connection_close(buffer),
+		MessageCollectorAdvance::Error(e) if e == CollectError::new(ParseError::IncompleteMessage, 44)
+	));
+}
+
+#[test]
+fn test_error_offsets() {
+	let (_, err) = collect_in_parts(&[
+		b"GET / HTTP/1.1\r\nHost: x\r\n",
+		b"Bad Name: y\r\n\r\n",
+	]);
+	assert_eq!(err, Some(CollectError::new(ParseError::WhitespaceBeforeColon, 28)));
+
+	let (_, err) = collect_in_parts(&[
+		b"GET / HTTP/1.1\r\nHost: x\ry\r\n\r\n",
+	]);
+	assert_eq!(err, Some(CollectError::new(ParseError::BareCR, 23)));
+
+	let mut long_line = b"GET / HTTP/1.1\r\nX-Long: ".to_vec();
+	long_line.resize(long_line.len() + CollectorLimits::default().max_header_line, b'a');
+	let (_, err) = collect_in_parts(&[&long_line]);
+	assert_eq!(err, Some(CollectError::new(ParseError::HeaderLineTooLong, 16)));
+
+	// offsets survive compaction of the caller's buffer
+	let mut collector = MessageCollector::new();
+	let mut buffer = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nab".to_vec();
+	collector.advance(&buffer, |_| Ok((Version::HTTP_1_1, BodyRule::UntilCloseIfUnframed)));
+	collector.compact(&mut buffer);
+	buffer.extend_from_slice(b"cXX");
+	assert!(matches!(
+		collector.advance(&buffer, |_| unreachable!()),
+		MessageCollectorAdvance::Error(e) if e == CollectError::new(ParseError::InvalidChunkDelimiter, 53)
+	));
+}
+
+#[test]
+fn test_non_ascii_input() {
+	let (collector, err) = collect_in_parts(&[
+		b"GET / HTTP/1.1\r\nX-Name: Zo\xc3\xab \xff\r\n\r\n",
+	]);
+	assert_eq!(err, None);
+	assert_eq!(collector.collected_headers.get_bytes("x-name"), Some(&b"Zo\xc3\xab \xff"[..]));
+	assert_eq!(collector.collected_headers.get("x-name"), Some("Zo\u{eb} \u{FFFD}"));
+
+	let (_, err) = collect_in_parts(&[
+		b"GET / HTTP/1.1\r\nX-N\xc3\xa4me: x\r\n\r\n",
+	]);
+	assert_eq!(err, Some(CollectError::new(ParseError::InvalidTokenChar, 19)));
+}
+
+#[test]
+fn test_limits() {
+	fn collect_with(limits: CollectorLimits, input: &[u8]) -> Option<CollectError> {
+		let mut collector = MessageCollector::with_limits(limits);
+		match collector.advance(input, |_| Ok((Version::HTTP_1_1, BodyRule::UntilCloseIfUnframed))) {
+			MessageCollectorAdvance::Error(e) => Some(e),
+			_ => None,
+		}
+	}
+	let limits = CollectorLimits {
+		max_first_line: 20,
+		max_header_line: 32,
+		max_header_count: 2,
+		max_preamble: 64,
+		max_chunk_line: 8,
+		max_body: 4,
+		max_interim_responses: 0,
+	};
+
+	assert_eq!(collect_with(limits, b"GET /a-very-long-target HTTP/1.1\r\n"),
+			   Some(CollectError::new(ParseError::FirstLineTooLong, 0)));
+	// no line end in sight is enough
+	assert_eq!(collect_with(limits, b"GET /a-very-long-target"),
+			   Some(CollectError::new(ParseError::FirstLineTooLong, 0)));
+	assert_eq!(collect_with(limits, b"GET / HTTP/1.1\r\nX-Long: 0123456789012345678901234567\r\n"),
+			   Some(CollectError::new(ParseError::HeaderLineTooLong, 16)));
+	assert_eq!(collect_with(limits, b"GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\nC: 3\r\n\r\n"),
+			   Some(CollectError::new(ParseError::TooManyHeaders, 28)));
+	assert_eq!(collect_with(limits, &[b"GET / HTTP/1.1\r\n".as_slice(), &[b"A: 01234567890123456789012345\r\n".as_slice(); 2].concat()].concat()),
+			   Some(CollectError::new(ParseError::PreambleTooLarge, 64)));
+	assert_eq!(collect_with(limits, b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n"),
+			   Some(CollectError::new(ParseError::BodyTooLarge, 38)));
+	assert_eq!(collect_with(limits, b"HTTP/1.1 200 OK\r\n\r\nhello"),
+			   Some(CollectError::new(ParseError::BodyTooLarge, 19)));
+	assert_eq!(collect_with(limits, b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n2\r\n"),
+			   Some(CollectError::new(ParseError::BodyTooLarge, 55)));
+	assert_eq!(collect_with(limits, b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3;ext=value\r\nabc\r\n"),
+			   Some(CollectError::new(ParseError::ChunkLineTooLong, 47)));
+	assert_eq!(collect_with(limits, b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3;ext=value"),
+			   Some(CollectError::new(ParseError::ChunkLineTooLong, 47)));
+
+	assert_eq!(collect_with(limits, b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\nbody"), None);
+	assert_eq!(collect_with(CollectorLimits::default(), b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n1\r\na\r\nffffffffffffffff\r\n"),
+			   Some(CollectError::new(ParseError::BodyTooLarge, 53)));
+
+	// the body and whatever follows it in the buffer aren't part of the preamble
+	let limits = CollectorLimits { max_body: usize::MAX, ..limits };
+	let body = [b'x'; 100];
+	assert_eq!(collect_with(limits, &[b"POST / HTTP/1.1\r\nContent-Length: 100\r\n\r\n".as_slice(), &body].concat()), None);
+	assert_eq!(collect_with(limits, &[b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n64\r\n".as_slice(), &body, b"\r\n0\r\n\r\n"].concat()), None);
+	assert_eq!(collect_with(limits, &[b"GET / HTTP/1.1\r\n\r\n".as_slice(); 10].concat()), None);
+	assert_eq!(ParseError::PreambleTooLarge.status_code(), StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE);
+}
+
+#[test]
+fn test_obs_fold() {
+	let input: &[u8] = b"GET / HTTP/1.1\r\nX-Folded: first\r\n  second\r\n\tthird  \r\nHost: a\r\n\r\n";
+
+	let (_, err) = collect_in_parts(&[input]);
+	assert_eq!(err, Some(CollectError::new(ParseError::ObsFold, 33)));
+
+	let mut collector = MessageCollector::new();
+	collector.set_config(ParserConfig::lenient());
+	assert!(matches!(
+		collector.advance(input, |_| Ok((Version::HTTP_1_1, BodyRule::EmptyIfUnframed))),
+		MessageCollectorAdvance::Finished { .. }
+	));
+	assert_eq!(collector.collected_headers.get("x-folded"), Some("first second third"));
+	assert_eq!(collector.collected_headers.len(), 2);
+
+	// whitespace-only continuations add nothing, not even the SP
+	let mut collector = MessageCollector::new();
+	collector.set_config(ParserConfig::lenient());
+	assert!(matches!(
+		collector.advance(b"GET / HTTP/1.1\r\nX: a\r\n   \r\nY:\r\n\tb\r\n\r\n", |_| Ok((Version::HTTP_1_1, BodyRule::EmptyIfUnframed))),
+		MessageCollectorAdvance::Finished { .. }
+	));
+	assert_eq!(collector.collected_headers.get("x"), Some("a"));
+	assert_eq!(collector.collected_headers.get("y"), Some("b"));
+
+	let mut collector = MessageCollector::new();
+	collector.set_config(ParserConfig::lenient());
+	assert!(matches!(
+		collector.advance(b"GET / HTTP/1.1\r\n folded: x\r\n\r\n", |_| Ok((Version::HTTP_1_1, BodyRule::EmptyIfUnframed))),
+		MessageCollectorAdvance::Error(e) if e == CollectError::new(ParseError::ObsFold, 16)
+	));
+}
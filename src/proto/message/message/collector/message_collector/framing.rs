@@ -0,0 +1,108 @@
+use super::MessageCollector;
+use crate::consts::Version;
+use crate::proto::parser::ParseError;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TransferStrategy {
+	None,
+	ContentLength(usize),
+	Chunked,
+	UntilClose,
+}
+
+/** What a message without Transfer-Encoding or Content-Length carries as its body */
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum BodyRule {
+	/** requests: no framing headers means no body */
+	EmptyIfUnframed,
+	/** responses: the body runs until the connection is closed */
+	UntilCloseIfUnframed,
+	/** responses to HEAD, 1xx, 204 and 304: never a body, whatever the headers say */
+	NoBody,
+}
+
+impl MessageCollector {
+	pub(super) fn transfer_strategy(&mut self) -> Result<TransferStrategy, ParseError> {
+		if self.body_rule == BodyRule::NoBody {
+			return Ok(TransferStrategy::None);
+		}
+
+		if self.is_chunked()? {
+			// the classic request smuggling vector, RFC 9112 6.1
+			if self.collected_headers.contains("content-length") {
+				if !self.config.allow_content_length_with_transfer_encoding {
+					return Err(ParseError::ConflictingFraming);
+				}
+				self.must_close = true;
+			}
+			// an HTTP/1.0 recipient may not know chunked at all, RFC 9112 6.1 again
+			if self.version == Version::HTTP_1_0 {
+				if !self.config.allow_transfer_encoding_in_http_1_0 {
+					return Err(ParseError::TransferEncodingInHttp10);
+				}
+				self.must_close = true;
+			}
+			return Ok(TransferStrategy::Chunked);
+		}
+
+		match self.content_length()? {
+			None => match self.body_rule {
+				BodyRule::EmptyIfUnframed => Ok(TransferStrategy::None),
+				BodyRule::UntilCloseIfUnframed => Ok(TransferStrategy::UntilClose),
+				BodyRule::NoBody => unreachable!(),
+			},
+			Some(0) => Ok(TransferStrategy::None),
+			Some(length) => Ok(TransferStrategy::ContentLength(length)),
+		}
+	}
+
+	/** Value of the Content-Length header(s); duplicates must all agree. */
+	fn content_length(&self) -> Result<Option<usize>, ParseError> {
+		let mut length: Option<usize> = None;
+
+		// a list of identical values ("5, 5") is tolerated, see RFC 9110 8.6
+		for element in self.collected_headers.get_all("content-length")
+			.flat_map(|v| v.split(',')) {
+			let parsed = parse_content_length_value(element.trim().as_bytes())?;
+
+			match length {
+				None => length = Some(parsed),
+				Some(l) if l == parsed => {}
+				Some(_) => return Err(ParseError::ConflictingContentLength),
+			}
+		}
+
+		Ok(length)
+	}
+
+	/** (!) currently, the only supported transfer coding is a single "chunked" */
+	fn is_chunked(&self) -> Result<bool, ParseError> {
+		let codings: Vec<&str> = self.collected_headers.get_all("transfer-encoding")
+			.flat_map(|v| v.split(','))
+			.map(|c| c.trim())
+			.filter(|c| !c.is_empty())
+			.collect();
+
+		let is_chunked = |c: &&str| c.eq_ignore_ascii_case("chunked");
+		match codings.as_slice() {
+			[] if !self.collected_headers.contains("transfer-encoding") => Ok(false),
+			// present but empty: a recipient skipping it would go by the Content-Length
+			[] if self.collected_headers.contains("content-length") => Err(ParseError::ConflictingFraming),
+			[c] if is_chunked(c) => Ok(true),
+			// whoever reads it differently ends the body somewhere else
+			[init @ .., _] if init.iter().any(is_chunked) => Err(ParseError::ChunkedNotFinal),
+			_ => Err(ParseError::UnsupportedTransferEncoding),
+		}
+	}
+}
+
+fn parse_content_length_value(value: &[u8]) -> Result<usize, ParseError> {
+	if value.is_empty() || !value.iter().all(u8::is_ascii_digit) {
+		return Err(ParseError::InvalidContentLength);
+	}
+
+	std::str::from_utf8(value)
+		.ok()
+		.and_then(|s| s.parse::<usize>().ok())
+		.ok_or(ParseError::InvalidContentLength)
+}
@@ -6,14 +6,10 @@ impl MessageCollector {
 	pub fn into_message(self, http_version: Version) -> Message {
 		Message {
 			version: http_version,
-			headers: self.collected_headers
-				.into_iter()
-				.map(|(hfn, hfv)| (
-					String::from_utf8_lossy(hfn.as_slice()).to_string(),
-					String::from_utf8_lossy(hfv.as_slice()).to_string(),
-				))
-				.collect(),
+			headers: self.collected_headers,
 			body: self.collected_body,
+			body_reader: None,
+			trailers: self.collected_trailers,
 		}
 	}
 }
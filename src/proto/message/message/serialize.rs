@@ -0,0 +1,152 @@
+use std::io::Read;
+use crate::consts::Version;
+use crate::header_map::HeaderMap;
+use crate::proto::parser::is_token;
+use crate::proto::message::{BodyRule, BuildError, Message};
+
+const READER_CHUNK_SIZE: usize = 0x2000;
+
+enum Framing {
+	ContentLength(Option<usize>),
+	Chunked,
+}
+
+/** A stray CRLF in either the name or the value would start a field of its own */
+pub(crate) fn check_field(name: &str, value: &[u8]) -> Result<(), BuildError> {
+	if !is_token(name.as_bytes()) {
+		return Err(BuildError::InvalidFieldName);
+	}
+	if value.iter().any(|c| matches!(c, b'\r' | b'\n' | b'\0')) {
+		return Err(BuildError::InvalidFieldValue);
+	}
+	Ok(())
+}
+
+fn write_fields(ret: &mut Vec<u8>, fields: &HeaderMap) -> Result<(), BuildError> {
+	for (k, v) in fields.iter_bytes() {
+		check_field(k, v)?;
+		ret.extend_from_slice(k.as_bytes());
+		ret.extend_from_slice(b": ");
+		ret.extend_from_slice(v);
+		ret.extend_from_slice(b"\r\n");
+	}
+	Ok(())
+}
+
+fn write_chunk(ret: &mut Vec<u8>, chunk: &[u8]) {
+	if chunk.is_empty() {
+		return;
+	}
+	ret.extend_from_slice(format!("{:x}\r\n", chunk.len()).as_bytes());
+	ret.extend_from_slice(chunk);
+	ret.extend_from_slice(b"\r\n");
+}
+
+impl Message {
+	/** Framing the user asked for with their own headers */
+	fn declared_framing(&self) -> Result<Option<Framing>, BuildError> {
+		let mut declared = None;
+
+		for (k, v) in &self.headers {
+			let framing = if k.eq_ignore_ascii_case("content-length") {
+				Framing::ContentLength(Some(
+					v.trim().parse::<usize>()
+						.map_err(|_| BuildError::InvalidContentLength)?
+				))
+			} else if k.eq_ignore_ascii_case("transfer-encoding") {
+				// HTTP/1.0 has no transfer codings
+				if !v.trim().eq_ignore_ascii_case("chunked") || self.version == Version::HTTP_1_0 {
+					return Err(BuildError::UnsupportedTransferEncoding);
+				}
+				Framing::Chunked
+			} else {
+				continue;
+			};
+
+			match (&declared, &framing) {
+				(None, _) => declared = Some(framing),
+				(Some(Framing::ContentLength(a)), Framing::ContentLength(b)) if a == b => {}
+				_ => return Err(BuildError::ConflictingFraming),
+			}
+		}
+
+		Ok(declared)
+	}
+
+	fn read_remaining_body(&mut self) -> Result<(), BuildError> {
+		if let Some(mut reader) = self.body_reader.take() {
+			reader.0.read_to_end(&mut self.body)
+				.map_err(|e| BuildError::Io(e.kind()))?;
+		}
+		Ok(())
+	}
+
+	/// Adds Content-Length, or chunked framing when the length isn't known up front
+	/// and the version has it, unless the headers already carry framing; `body_rule`
+	/// is what the receiver would assume of a message without any.
+	pub fn into_bytes(mut self, body_rule: BodyRule) -> Result<Vec<u8>, BuildError> {
+		let mut ret = Vec::new();
+
+		let framing = match (self.declared_framing()?, body_rule) {
+			(_, BodyRule::NoBody) => {
+				self.read_remaining_body()?;
+				if !self.body.is_empty() {
+					return Err(BuildError::BodyNotAllowed);
+				}
+				write_fields(&mut ret, &self.headers)?;
+				ret.extend_from_slice(b"\r\n");
+				return Ok(ret);
+			}
+			(Some(declared), _) => declared,
+			(None, _) if self.body_reader.is_some() && self.version != Version::HTTP_1_0 => {
+				self.headers.append("Transfer-Encoding", "chunked");
+				Framing::Chunked
+			}
+			(None, BodyRule::EmptyIfUnframed) if self.body.is_empty() && self.body_reader.is_none() =>
+				Framing::ContentLength(None),
+			(None, _) => {
+				self.read_remaining_body()?;
+				self.headers.append("Content-Length", &self.body.len().to_string());
+				Framing::ContentLength(Some(self.body.len()))
+			}
+		};
+
+		match framing {
+			Framing::ContentLength(declared) => {
+				self.read_remaining_body()?;
+
+				let actual = self.body.len();
+				if let Some(declared) = declared.filter(|d| *d != actual) {
+					return Err(BuildError::ContentLengthMismatch { declared, actual });
+				}
+
+				write_fields(&mut ret, &self.headers)?;
+				ret.extend_from_slice(b"\r\n");
+				ret.extend_from_slice(self.body.as_slice());
+			}
+			Framing::Chunked => {
+				write_fields(&mut ret, &self.headers)?;
+				ret.extend_from_slice(b"\r\n");
+				write_chunk(&mut ret, self.body.as_slice());
+
+				if let Some(mut reader) = self.body_reader.take() {
+					let mut buff = vec![0; READER_CHUNK_SIZE];
+					loop {
+						match reader.0.read(&mut buff) {
+							Ok(0) => break,
+							Ok(n) => write_chunk(&mut ret, &buff[..n]),
+							Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+							Err(e) => return Err(BuildError::Io(e.kind())),
+						}
+					}
+				}
+
+				ret.extend_from_slice(b"0\r\n");
+				write_fields(&mut ret, &self.trailers)?;
+				ret.extend_from_slice(b"\r\n");
+			}
+		}
+
+		Ok(ret)
+	}
+}
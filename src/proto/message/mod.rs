@@ -1,2 +1,3 @@
+#[allow(clippy::module_inception)]
 mod message;
 pub use message::*;
@@ -1,7 +1,17 @@
 pub mod consts;
+pub mod config;
 pub mod url;
+pub mod header_map;
+pub mod media_type;
+pub mod multipart;
+pub mod form_urlencoded;
 mod buffer_reader;
 mod message;
 mod parser;
+
+pub use parser::{CollectError, ParseError};
+pub use message::BuildError;
+
 pub mod request;
 pub mod response;
+pub mod connection;
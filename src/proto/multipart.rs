@@ -0,0 +1,11 @@
+mod content_disposition;
+mod parser;
+mod builder;
+mod stream;
+mod spooler;
+
+pub use content_disposition::ContentDisposition;
+pub use parser::*;
+pub use builder::{MultipartBody, MultipartBuilder};
+pub use stream::{MultipartEvent, MultipartStream, PartHead};
+pub use spooler::*;
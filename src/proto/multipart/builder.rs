@@ -0,0 +1,190 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::header_map::HeaderMap;
+use crate::media_type::MediaType;
+use crate::proto::message::{check_field, BuildError};
+use crate::proto::multipart::ContentDisposition;
+
+struct BuilderPart {
+	headers: HeaderMap,
+	body: Vec<u8>,
+}
+
+/** A multipart body with the Content-Type that carries its boundary */
+#[derive(Debug, Clone)]
+pub struct MultipartBody {
+	content_type: MediaType,
+	body: Vec<u8>,
+}
+
+impl MultipartBody {
+	pub fn content_type(&self) -> &MediaType {
+		&self.content_type
+	}
+	pub fn body(&self) -> &[u8] {
+		self.body.as_slice()
+	}
+	pub fn into_parts(self) -> (MediaType, Vec<u8>) {
+		(self.content_type, self.body)
+	}
+}
+
+pub struct MultipartBuilder {
+	subtype: String,
+	parts: Vec<BuilderPart>,
+}
+
+impl Default for MultipartBuilder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl MultipartBuilder {
+	/** multipart/form-data */
+	pub fn new() -> Self {
+		Self::with_subtype("form-data")
+	}
+
+	pub fn with_subtype(subtype: &str) -> Self {
+		Self {
+			subtype: subtype.to_string(),
+			parts: vec![],
+		}
+	}
+
+	pub fn add_part(&mut self, headers: HeaderMap, body: &[u8]) -> &mut Self {
+		self.parts.push(BuilderPart {
+			headers,
+			body: body.to_vec(),
+		});
+		self
+	}
+
+	pub fn add_text(&mut self, name: &str, value: &str) -> &mut Self {
+		let mut headers = HeaderMap::new();
+		headers.append("Content-Disposition", &ContentDisposition::form_data(name).to_string());
+		self.add_part(headers, value.as_bytes())
+	}
+
+	pub fn add_file(&mut self, name: &str, filename: &str, content_type: &MediaType, body: &[u8])
+					-> &mut Self {
+		let disposition = ContentDisposition::form_data(name)
+			.with_param("filename", filename);
+
+		let mut headers = HeaderMap::new();
+		headers.append("Content-Disposition", &disposition.to_string());
+		headers.append("Content-Type", &content_type.to_string());
+		self.add_part(headers, body)
+	}
+
+	/** Fails on part headers that would break out of their line, as `into_bytes` does */
+	pub fn build(self) -> Result<MultipartBody, BuildError> {
+		let boundary = pick_boundary(&self.parts, std::iter::repeat_with(random_boundary));
+
+		let mut body = Vec::new();
+		for part in self.parts.iter() {
+			body.extend_from_slice(format!("--{boundary}\r\n").as_bytes());
+			for (k, v) in &part.headers {
+				check_field(k, v.as_bytes())?;
+				body.extend_from_slice(format!("{k}: {v}\r\n").as_bytes());
+			}
+			body.extend_from_slice(b"\r\n");
+			body.extend_from_slice(part.body.as_slice());
+			body.extend_from_slice(b"\r\n");
+		}
+		body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+
+		Ok(MultipartBody {
+			content_type: MediaType::new("multipart", &self.subtype)
+				.with_param("boundary", &boundary),
+			body,
+		})
+	}
+}
+
+fn random_boundary() -> String {
+	static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+	let mut hasher = RandomState::new().build_hasher();
+	hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+	if let Ok(t) = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
+		hasher.write_u128(t.as_nanos());
+	}
+
+	format!("----HttpBoundary{:016x}", hasher.finish())
+}
+
+/** First candidate that appears in none of the parts */
+fn pick_boundary(parts: &[BuilderPart], mut candidates: impl Iterator<Item=String>) -> String {
+	let contains = |haystack: &[u8], needle: &[u8]| {
+		haystack.windows(needle.len()).any(|w| w == needle)
+	};
+
+	candidates
+		.find(|candidate| parts.iter().all(|part| {
+			!contains(&part.body, candidate.as_bytes())
+				&& part.headers.iter().all(|(_, v)| !v.contains(candidate.as_str()))
+		}))
+		.unwrap()
+}
+
+#[test]
+fn test_multipart_builder() {
+	use crate::proto::multipart::parse_multipart;
+
+	let mut builder = MultipartBuilder::new();
+	builder
+		.add_text("text-field", "some text")
+		.add_file("file", "a \"b\".txt", &MediaType::new("text", "plain"), b"line 1\r\nline 2\r\n");
+	let multipart = builder.build().unwrap();
+
+	assert_eq!(multipart.content_type().essence(), "multipart/form-data");
+	let boundary = multipart.content_type().boundary().unwrap();
+
+	let parts = parse_multipart(multipart.body(), boundary).unwrap();
+	assert_eq!(parts.len(), 2);
+	assert_eq!(parts[0].name(), Some("text-field"));
+	assert_eq!(parts[0].body(), b"some text");
+	// as browsers do it, HTML's multipart/form-data encoding
+	assert_eq!(parts[1].filename(), Some("a %22b%22.txt"));
+	assert_eq!(parts[1].content_type(), Some(MediaType::new("text", "plain")));
+	assert_eq!(parts[1].body(), b"line 1\r\nline 2\r\n");
+}
+
+#[test]
+fn test_multipart_builder_injection() {
+	use crate::proto::multipart::parse_multipart;
+
+	let mut builder = MultipartBuilder::new();
+	builder.add_file(
+		"file\r\nX-Injected: 1",
+		"evil.txt\"\r\nContent-Type: text/html\r\n\r\n<script>",
+		&MediaType::new("text", "plain"),
+		b"body",
+	);
+	let multipart = builder.build().unwrap();
+
+	let parts = parse_multipart(multipart.body(), multipart.content_type().boundary().unwrap()).unwrap();
+	assert_eq!(parts.len(), 1);
+	assert_eq!(parts[0].headers().len(), 2);
+	assert_eq!(parts[0].name(), Some("file%0D%0AX-Injected: 1"));
+	assert_eq!(parts[0].filename(), Some("evil.txt%22%0D%0AContent-Type: text/html%0D%0A%0D%0A<script>"));
+	assert_eq!(parts[0].body(), b"body");
+
+	let mut headers = HeaderMap::new();
+	headers.append("X-Note", "a\r\nX-Injected: 1");
+	let mut builder = MultipartBuilder::new();
+	builder.add_part(headers, b"body");
+	assert_eq!(builder.build().err(), Some(BuildError::InvalidFieldValue));
+}
+
+#[test]
+fn test_boundary_collision() {
+	let mut builder = MultipartBuilder::new();
+	builder.add_text("a", "contains --first-- in text");
+
+	let candidates = ["first", "second"].into_iter().map(String::from);
+	assert_eq!(pick_boundary(&builder.parts, candidates), "second");
+}
@@ -0,0 +1,75 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use crate::proto::media_type::{parse_parameters, write_parameter};
+use crate::proto::parser::{is_token, ParseError};
+
+/** `form-data; name="field"; filename="a.txt"`, see RFC 6266 and RFC 7578 */
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ContentDisposition {
+	disposition: String,
+	parameters: Vec<(String, String)>,
+}
+
+impl ContentDisposition {
+	pub fn form_data(name: &str) -> Self {
+		Self {
+			disposition: "form-data".to_string(),
+			parameters: vec![("name".to_string(), name.to_string())],
+		}
+	}
+
+	pub fn with_param(mut self, name: &str, value: &str) -> Self {
+		self.parameters.push((name.to_ascii_lowercase(), value.to_string()));
+		self
+	}
+
+	pub fn disposition(&self) -> &str {
+		self.disposition.as_str()
+	}
+	pub fn param(&self, name: &str) -> Option<&str> {
+		self.parameters
+			.iter()
+			.find(|(n, _)| n.eq_ignore_ascii_case(name))
+			.map(|(_, v)| v.as_str())
+	}
+	pub fn name(&self) -> Option<&str> {
+		self.param("name")
+	}
+	pub fn filename(&self) -> Option<&str> {
+		self.param("filename")
+	}
+}
+
+impl FromStr for ContentDisposition {
+	type Err = ParseError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let s = s.trim();
+		let end = s.find(|c: char| c == ';' || c.is_ascii_whitespace()).unwrap_or(s.len());
+
+		if !is_token(&s.as_bytes()[..end]) {
+			return Err(ParseError::InvalidMultipart);
+		}
+
+		Ok(Self {
+			disposition: s[..end].to_ascii_lowercase(),
+			parameters: parse_parameters(&s.as_bytes()[end..])
+				.map_err(|_| ParseError::InvalidMultipart)?,
+		})
+	}
+}
+
+impl Display for ContentDisposition {
+	/** `"`, CR and LF in values are percent-encoded, as HTML's multipart/form-data encoding has it */
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.disposition)?;
+		for (name, value) in self.parameters.iter() {
+			let value = value
+				.replace('"', "%22")
+				.replace('\r', "%0D")
+				.replace('\n', "%0A");
+			write_parameter(f, name, &value)?;
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,204 @@
+use crate::header_map::HeaderMap;
+use crate::media_type::MediaType;
+use crate::proto::message::Message;
+use crate::proto::multipart::ContentDisposition;
+use crate::proto::parser;
+use crate::config::ParserConfig;
+use crate::proto::parser::{HeaderLineParseResult, ParseError};
+
+#[derive(Debug)]
+pub struct Part<'a> {
+	headers: HeaderMap,
+	disposition: Option<ContentDisposition>,
+	body: &'a [u8],
+}
+
+impl<'a> Part<'a> {
+	pub fn headers(&self) -> &HeaderMap {
+		&self.headers
+	}
+	pub fn disposition(&self) -> Option<&ContentDisposition> {
+		self.disposition.as_ref()
+	}
+	pub fn name(&self) -> Option<&str> {
+		self.disposition.as_ref()?.name()
+	}
+	pub fn filename(&self) -> Option<&str> {
+		self.disposition.as_ref()?.filename()
+	}
+	pub fn content_type(&self) -> Option<MediaType> {
+		self.headers.get("content-type")?.parse().ok()
+	}
+	pub fn body(&self) -> &'a [u8] {
+		self.body
+	}
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+	haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+pub(crate) fn parse_part_headers(block: &[u8]) -> Result<HeaderMap, ParseError> {
+	let mut headers = HeaderMap::new();
+
+	for line in block.split(|b| *b == b'\n') {
+		let line = line.strip_suffix(b"\r").unwrap_or(line);
+		match parser::parse_header_line(line, &ParserConfig::lenient()) {
+			HeaderLineParseResult::Empty => {}
+			HeaderLineParseResult::Err { .. } => return Err(ParseError::InvalidMultipart),
+			HeaderLineParseResult::Ok { field_name, field_value } => headers.append(
+				&String::from_utf8_lossy(field_name),
+				&String::from_utf8_lossy(field_value),
+			),
+		}
+	}
+
+	Ok(headers)
+}
+
+fn parse_part(part: &[u8]) -> Result<Part<'_>, ParseError> {
+	// a part without any header fields starts with the blank line right away
+	let (header_block, body) = match part.strip_prefix(b"\r\n") {
+		Some(body) => (&b""[..], body),
+		None => {
+			let end = find(part, b"\r\n\r\n").ok_or(ParseError::InvalidMultipart)?;
+			(&part[..end], &part[end + 4..])
+		}
+	};
+
+	let headers = parse_part_headers(header_block)?;
+	let disposition = match headers.get("content-disposition") {
+		None => None,
+		Some(v) => Some(v.parse()?),
+	};
+
+	Ok(Part {
+		headers,
+		disposition,
+		body,
+	})
+}
+
+/** bchars, RFC 2046 5.1.1; an empty boundary would make every CRLF "--" a delimiter */
+pub(crate) fn check_boundary(boundary: &str) -> Result<(), ParseError> {
+	let is_bchar = |c: u8| c.is_ascii_alphanumeric() || b"'()+_,-./:=? ".contains(&c);
+	if (1..=70).contains(&boundary.len()) && !boundary.ends_with(' ') && boundary.bytes().all(is_bchar) {
+		Ok(())
+	} else {
+		Err(ParseError::InvalidBoundary)
+	}
+}
+
+/** Splits a multipart body on `boundary`, skipping the preamble and the epilogue */
+pub fn parse_multipart<'a>(body: &'a [u8], boundary: &str) -> Result<Vec<Part<'a>>, ParseError> {
+	check_boundary(boundary)?;
+
+	let dash_boundary = format!("--{boundary}").into_bytes();
+	let delimiter = format!("\r\n--{boundary}").into_bytes();
+
+	let mut pos = if body.starts_with(&dash_boundary) {
+		dash_boundary.len()
+	} else {
+		find(body, &delimiter).ok_or(ParseError::MissingClosingBoundary)? + delimiter.len()
+	};
+
+	let mut parts = Vec::new();
+	loop {
+		if body[pos..].starts_with(b"--") {
+			return Ok(parts);
+		}
+
+		// transport padding may follow a delimiter
+		while matches!(body.get(pos), Some(b' ' | b'\t')) {
+			pos += 1;
+		}
+		if !body[pos..].starts_with(b"\r\n") {
+			return Err(ParseError::InvalidMultipart);
+		}
+		pos += 2;
+
+		let part_len = find(&body[pos..], &delimiter).ok_or(ParseError::MissingClosingBoundary)?;
+		parts.push(parse_part(&body[pos..pos + part_len])?);
+
+		pos += part_len + delimiter.len();
+	}
+}
+
+impl Message {
+	/** Parts of a multipart body, the boundary is taken from Content-Type */
+	pub fn multipart(&self) -> Result<Vec<Part<'_>>, ParseError> {
+		let content_type = self.content_type()
+			.filter(MediaType::is_multipart)
+			.ok_or(ParseError::NotMultipart)?;
+		let boundary = content_type.boundary().ok_or(ParseError::MissingBoundary)?;
+
+		parse_multipart(self.body(), boundary)
+	}
+}
+
+#[test]
+fn test_parse_multipart() {
+	let body = b"this is the preamble\r\n\
+		------boundary\r\n\
+		Content-Disposition: form-data; name=\"text-field\"\r\n\
+		\r\n\
+		some text\r\n\
+		------boundary  \r\n\
+		Content-Disposition: form-data; name=\"file\"; filename=\"a b.txt\"\r\n\
+		Content-Type: text/plain\r\n\
+		\r\n\
+		line 1\r\nline 2\r\n\r\n\
+		------boundary\r\n\
+		\r\n\
+		no headers\r\n\
+		------boundary--\r\n\
+		this is the epilogue";
+
+	let parts = parse_multipart(body, "----boundary").unwrap();
+	assert_eq!(parts.len(), 3);
+
+	assert_eq!(parts[0].name(), Some("text-field"));
+	assert_eq!(parts[0].filename(), None);
+	assert_eq!(parts[0].body(), b"some text");
+
+	assert_eq!(parts[1].name(), Some("file"));
+	assert_eq!(parts[1].filename(), Some("a b.txt"));
+	assert_eq!(parts[1].content_type().unwrap().essence(), "text/plain");
+	assert_eq!(parts[1].body(), b"line 1\r\nline 2\r\n");
+
+	assert!(parts[2].headers().is_empty());
+	assert_eq!(parts[2].body(), b"no headers");
+}
+
+#[test]
+fn test_parse_multipart_errors() {
+	let unclosed = b"--b\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\nvalue";
+	assert_eq!(parse_multipart(unclosed, "b").err(), Some(ParseError::MissingClosingBoundary));
+
+	let no_boundary_at_all = b"just text";
+	assert_eq!(
+		parse_multipart(no_boundary_at_all, "b").err(),
+		Some(ParseError::MissingClosingBoundary)
+	);
+
+	let garbage_after_delimiter = b"--bX\r\n\r\nvalue\r\n--b--";
+	assert_eq!(
+		parse_multipart(garbage_after_delimiter, "b").err(),
+		Some(ParseError::InvalidMultipart)
+	);
+
+	let bad_header = b"--b\r\nnot a header\r\n\r\nvalue\r\n--b--";
+	assert_eq!(parse_multipart(bad_header, "b").err(), Some(ParseError::InvalidMultipart));
+}
+
+#[test]
+fn test_invalid_boundary() {
+	let body = b"--\r\n\r\nvalue\r\n----\r\n";
+	assert_eq!(parse_multipart(body, "").err(), Some(ParseError::InvalidBoundary));
+	assert_eq!(parse_multipart(body, "b ").err(), Some(ParseError::InvalidBoundary));
+	assert_eq!(parse_multipart(body, "a\r\nb").err(), Some(ParseError::InvalidBoundary));
+	assert_eq!(parse_multipart(body, &"b".repeat(71)).err(), Some(ParseError::InvalidBoundary));
+
+	assert!(check_boundary(&"b".repeat(70)).is_ok());
+	assert!(check_boundary("'()+_,-./:=? x").is_ok());
+}
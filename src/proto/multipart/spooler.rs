@@ -0,0 +1,267 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::proto::multipart::{MultipartEvent, MultipartStream, PartHead};
+use crate::proto::parser::ParseError;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum SpoolError {
+	Parse(ParseError),
+	Io(std::io::ErrorKind),
+	FileTooLarge,
+	FieldTooLarge,
+}
+
+impl From<ParseError> for SpoolError {
+	fn from(e: ParseError) -> Self {
+		SpoolError::Parse(e)
+	}
+}
+
+impl From<std::io::Error> for SpoolError {
+	fn from(e: std::io::Error) -> Self {
+		SpoolError::Io(e.kind())
+	}
+}
+
+/** A part without a filename, kept in memory */
+#[derive(Debug)]
+pub struct SpooledField {
+	pub head: PartHead,
+	pub value: Vec<u8>,
+}
+
+/** A file part written to disk; removing the file is up to the caller */
+#[derive(Debug)]
+pub struct SpooledFile {
+	pub head: PartHead,
+	pub path: PathBuf,
+	pub size: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct SpoolResult {
+	pub fields: Vec<SpooledField>,
+	pub files: Vec<SpooledFile>,
+}
+
+enum CurrentPart {
+	Field(SpooledField),
+	File(SpooledFile, File),
+}
+
+/// Drives a `MultipartStream`, writing every part with a filename into its own
+/// temporary file and keeping the other parts in memory.
+pub struct MultipartSpooler {
+	stream: MultipartStream,
+	dir: PathBuf,
+	max_file_size: u64,
+	max_field_size: usize,
+
+	current: Option<CurrentPart>,
+	result: SpoolResult,
+	/** the first error, the stream can't be picked up after it */
+	failed: Option<SpoolError>,
+}
+
+fn create_temp_file(dir: &Path) -> std::io::Result<(PathBuf, File)> {
+	static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+	loop {
+		let path = dir.join(format!(
+			"http-upload-{}-{}",
+			std::process::id(),
+			COUNTER.fetch_add(1, Ordering::Relaxed)
+		));
+		match OpenOptions::new().write(true).create_new(true).open(&path) {
+			Ok(file) => return Ok((path, file)),
+			Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+			Err(e) => return Err(e),
+		}
+	}
+}
+
+impl MultipartSpooler {
+	/** Fails on a boundary RFC 2046 doesn't allow, see [`MultipartStream::new`] */
+	pub fn new(boundary: &str) -> Result<Self, ParseError> {
+		Ok(Self {
+			stream: MultipartStream::new(boundary)?,
+			dir: std::env::temp_dir(),
+			max_file_size: u64::MAX,
+			max_field_size: 0x10000,
+			current: None,
+			result: SpoolResult::default(),
+			failed: None,
+		})
+	}
+
+	pub fn set_dir(&mut self, dir: &Path) -> &mut Self {
+		self.dir = dir.to_path_buf();
+		self
+	}
+	pub fn set_max_file_size(&mut self, max_file_size: u64) -> &mut Self {
+		self.max_file_size = max_file_size;
+		self
+	}
+	pub fn set_max_field_size(&mut self, max_field_size: usize) -> &mut Self {
+		self.max_field_size = max_field_size;
+		self
+	}
+
+	/// On error every file written so far is removed, the upload is of no use;
+	/// later pushes return the same error.
+	pub fn push(&mut self, bytes: &[u8]) -> Result<(), SpoolError> {
+		if let Some(e) = self.failed {
+			return Err(e);
+		}
+
+		let result = self.stream.push(bytes)
+			.map_err(SpoolError::from)
+			.and_then(|events| events
+				.into_iter()
+				.try_for_each(|event| self.on_event(event)));
+
+		if let Err(e) = result {
+			self.failed = Some(e);
+			self.remove_files();
+		}
+
+		result
+	}
+
+	/** Files of an upload that didn't finish are removed, here or once the spooler is dropped */
+	pub fn finish(mut self) -> Result<SpoolResult, SpoolError> {
+		if let Some(e) = self.failed {
+			return Err(e);
+		}
+		self.stream.finish()?;
+		Ok(std::mem::take(&mut self.result))
+	}
+
+	fn remove_files(&mut self) {
+		if let Some(CurrentPart::File(file, _)) = self.current.take() {
+			let _ = std::fs::remove_file(file.path);
+		}
+		for file in self.result.files.drain(..) {
+			let _ = std::fs::remove_file(file.path);
+		}
+	}
+
+	fn on_event(&mut self, event: MultipartEvent) -> Result<(), SpoolError> {
+		match event {
+			MultipartEvent::PartStart(head) => {
+				self.current = Some(match head.filename() {
+					None => CurrentPart::Field(SpooledField {
+						head,
+						value: Vec::new(),
+					}),
+					Some(_) => {
+						let (path, file) = create_temp_file(&self.dir)?;
+						CurrentPart::File(SpooledFile { head, path, size: 0 }, file)
+					}
+				});
+			}
+			// the stream only yields these within a part, still no reason to panic
+			MultipartEvent::Data(data) => match self.current.as_mut() {
+				None => return Err(SpoolError::Parse(ParseError::InvalidMultipart)),
+				Some(CurrentPart::Field(field)) => {
+					if field.value.len() + data.len() > self.max_field_size {
+						return Err(SpoolError::FieldTooLarge);
+					}
+					field.value.extend_from_slice(&data);
+				}
+				Some(CurrentPart::File(spooled, file)) => {
+					spooled.size += data.len() as u64;
+					if spooled.size > self.max_file_size {
+						return Err(SpoolError::FileTooLarge);
+					}
+					file.write_all(&data)?;
+				}
+			},
+			MultipartEvent::PartEnd => match self.current.take() {
+				None => return Err(SpoolError::Parse(ParseError::InvalidMultipart)),
+				Some(CurrentPart::Field(field)) => self.result.fields.push(field),
+				Some(CurrentPart::File(spooled, mut file)) => {
+					file.flush()?;
+					self.result.files.push(spooled);
+				}
+			},
+		}
+		Ok(())
+	}
+}
+
+impl Drop for MultipartSpooler {
+	fn drop(&mut self) {
+		self.remove_files();
+	}
+}
+
+#[test]
+fn test_multipart_spooler() {
+	let body: &[u8] = b"--b\r\n\
+		Content-Disposition: form-data; name=\"text-field\"\r\n\
+		\r\n\
+		small\r\n\
+		--b\r\n\
+		Content-Disposition: form-data; name=\"file\"; filename=\"big.bin\"\r\n\
+		\r\n\
+		0123456789abcdef\r\n\
+		--b--\r\n";
+
+	let mut spooler = MultipartSpooler::new("b").unwrap();
+	for piece in body.chunks(5) {
+		spooler.push(piece).unwrap();
+	}
+	let result = spooler.finish().unwrap();
+
+	assert_eq!(result.fields.len(), 1);
+	assert_eq!(result.fields[0].head.name(), Some("text-field"));
+	assert_eq!(result.fields[0].value, b"small");
+
+	assert_eq!(result.files.len(), 1);
+	let file = &result.files[0];
+	assert_eq!(file.head.filename(), Some("big.bin"));
+	assert_eq!(file.size, 16);
+	assert_eq!(std::fs::read(&file.path).unwrap(), b"0123456789abcdef");
+	std::fs::remove_file(&file.path).unwrap();
+
+	let mut spooler = MultipartSpooler::new("b").unwrap();
+	spooler.set_max_file_size(10);
+	let results: Vec<_> = body.chunks(5).map(|piece| spooler.push(piece)).collect();
+	let first_error = results.iter().position(Result::is_err).unwrap();
+	assert!(first_error < results.len() - 1);
+	// the rest of the part keeps coming, and keeps failing the same way
+	assert!(results[first_error..].iter().all(|r| *r == Err(SpoolError::FileTooLarge)));
+	assert_eq!(spooler.finish().unwrap_err(), SpoolError::FileTooLarge);
+
+	// nothing of a truncated upload is left behind
+	let dir = std::env::temp_dir().join(format!("http-spooler-test-{}", std::process::id()));
+	std::fs::create_dir_all(&dir).unwrap();
+	let mut spooler = MultipartSpooler::new("b").unwrap();
+	spooler.set_dir(&dir);
+	spooler.push(&body[..body.len() - 20]).unwrap();
+	assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+	assert_eq!(spooler.finish().unwrap_err(), SpoolError::Parse(ParseError::MissingClosingBoundary));
+	assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+	let file_then_field: &[u8] = b"--b\r\n\
+		Content-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\n\
+		\r\n\
+		done\r\n\
+		--b\r\n\
+		Content-Disposition: form-data; name=\"text-field\"\r\n\
+		\r\n\
+		too long\r\n\
+		--b--\r\n";
+	let mut spooler = MultipartSpooler::new("b").unwrap();
+	spooler.set_dir(&dir).set_max_field_size(3);
+	assert_eq!(spooler.push(file_then_field), Err(SpoolError::FieldTooLarge));
+	assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+	std::fs::remove_dir(&dir).unwrap();
+
+	let mut spooler = MultipartSpooler::new("b").unwrap();
+	spooler.set_max_field_size(3);
+	assert_eq!(spooler.push(body), Err(SpoolError::FieldTooLarge));
+}
@@ -0,0 +1,235 @@
+use crate::header_map::HeaderMap;
+use crate::media_type::MediaType;
+use crate::proto::multipart::ContentDisposition;
+use crate::proto::multipart::parser::{check_boundary, parse_part_headers};
+use crate::proto::parser::ParseError;
+
+/** Part header blocks larger than this are refused instead of buffered */
+const MAX_PART_HEADERS_LENGTH: usize = 0x4000;
+
+/** Header fields of a part, known once the part starts */
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PartHead {
+	headers: HeaderMap,
+	disposition: Option<ContentDisposition>,
+}
+
+impl PartHead {
+	pub fn headers(&self) -> &HeaderMap {
+		&self.headers
+	}
+	pub fn name(&self) -> Option<&str> {
+		self.disposition.as_ref()?.name()
+	}
+	pub fn filename(&self) -> Option<&str> {
+		self.disposition.as_ref()?.filename()
+	}
+	pub fn content_type(&self) -> Option<MediaType> {
+		self.headers.get("content-type")?.parse().ok()
+	}
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum MultipartEvent {
+	PartStart(PartHead),
+	Data(Vec<u8>),
+	PartEnd,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum StreamState {
+	Preamble,
+	AfterDelimiter,
+	PartHeaders,
+	PartBody,
+	Epilogue,
+}
+
+/// Multipart parser fed in pieces, e.g. with what `Collector::take_body` returns,
+/// so that no part has to be held in memory as a whole.
+pub struct MultipartStream {
+	state: StreamState,
+	delimiter: Vec<u8>,
+	buffer: Vec<u8>,
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+	haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+impl MultipartStream {
+	/** `boundary` as taken from Content-Type, fails if RFC 2046 doesn't allow it */
+	pub fn new(boundary: &str) -> Result<Self, ParseError> {
+		check_boundary(boundary)?;
+		Ok(Self {
+			state: StreamState::Preamble,
+			// the leading CRLF belongs to the delimiter, not to the preceding part
+			delimiter: format!("\r\n--{boundary}").into_bytes(),
+			buffer: b"\r\n".to_vec(),
+		})
+	}
+
+	pub fn is_finished(&self) -> bool {
+		self.state == StreamState::Epilogue
+	}
+
+	pub fn push(&mut self, bytes: &[u8]) -> Result<Vec<MultipartEvent>, ParseError> {
+		use StreamState::*;
+
+		let mut events = Vec::new();
+		if self.state == Epilogue {
+			return Ok(events);
+		}
+		self.buffer.extend_from_slice(bytes);
+
+		loop {
+			match self.state {
+				Preamble => match find(&self.buffer, &self.delimiter) {
+					Some(i) => {
+						self.buffer.drain(..i + self.delimiter.len());
+						self.state = AfterDelimiter;
+					}
+					None => {
+						let keep = self.delimiter.len() - 1;
+						let discard = self.buffer.len().saturating_sub(keep);
+						self.buffer.drain(..discard);
+						return Ok(events);
+					}
+				},
+				AfterDelimiter => {
+					if self.buffer.len() < 2 {
+						return Ok(events);
+					}
+					if self.buffer.starts_with(b"--") {
+						self.buffer.clear();
+						self.state = Epilogue;
+						return Ok(events);
+					}
+
+					// transport padding may follow a delimiter
+					let padding = self.buffer.iter()
+						.position(|b| !matches!(b, b' ' | b'\t'))
+						.unwrap_or(self.buffer.len());
+					match self.buffer[padding..].first() {
+						None => return Ok(events),
+						Some(b'\r') if self.buffer.len() < padding + 2 => return Ok(events),
+						Some(b'\r') if self.buffer[padding + 1] == b'\n' => {
+							self.buffer.drain(..padding + 2);
+							self.state = PartHeaders;
+						}
+						Some(_) => return Err(ParseError::InvalidMultipart),
+					}
+				}
+				PartHeaders => {
+					let (header_block_end, body_start) = if self.buffer.starts_with(b"\r\n") {
+						(0, 2)
+					} else if let Some(i) = find(&self.buffer, b"\r\n\r\n") {
+						(i, i + 4)
+					} else if self.buffer.len() > MAX_PART_HEADERS_LENGTH {
+						return Err(ParseError::InvalidMultipart);
+					} else {
+						return Ok(events);
+					};
+
+					let headers = parse_part_headers(&self.buffer[..header_block_end])?;
+					let disposition = match headers.get("content-disposition") {
+						None => None,
+						Some(v) => Some(v.parse()?),
+					};
+					self.buffer.drain(..body_start);
+
+					events.push(MultipartEvent::PartStart(PartHead {
+						headers,
+						disposition,
+					}));
+					self.state = PartBody;
+				}
+				PartBody => match find(&self.buffer, &self.delimiter) {
+					Some(i) => {
+						if i > 0 {
+							events.push(MultipartEvent::Data(self.buffer[..i].to_vec()));
+						}
+						events.push(MultipartEvent::PartEnd);
+						self.buffer.drain(..i + self.delimiter.len());
+						self.state = AfterDelimiter;
+					}
+					None => {
+						// the end of the buffer may be the start of a delimiter
+						let keep = self.delimiter.len() - 1;
+						let available = self.buffer.len().saturating_sub(keep);
+						if available > 0 {
+							events.push(MultipartEvent::Data(
+								self.buffer.drain(..available).collect()
+							));
+						}
+						return Ok(events);
+					}
+				},
+				Epilogue => return Ok(events),
+			}
+		}
+	}
+
+	/** To be called after the last push, fails if the closing delimiter never came */
+	pub fn finish(&self) -> Result<(), ParseError> {
+		match self.state {
+			StreamState::Epilogue => Ok(()),
+			_ => Err(ParseError::MissingClosingBoundary),
+		}
+	}
+}
+
+#[test]
+fn test_multipart_stream() {
+	let body: &[u8] = b"preamble\r\n\
+		--xyz\r\n\
+		Content-Disposition: form-data; name=\"field\"\r\n\
+		\r\n\
+		value\r\n\
+		--xyz\r\n\
+		Content-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\n\
+		\r\n\
+		file contents with \r\n--xy not quite a delimiter\r\n\
+		--xyz--\r\n\
+		epilogue";
+
+	// every split point must give the same result
+	for piece_len in 1..body.len() {
+		let mut stream = MultipartStream::new("xyz").unwrap();
+		let mut events = Vec::new();
+		for piece in body.chunks(piece_len) {
+			events.extend(stream.push(piece).unwrap());
+		}
+		assert_eq!(stream.finish(), Ok(()));
+
+		let mut names = Vec::new();
+		let mut bodies = Vec::new();
+		for event in events {
+			match event {
+				MultipartEvent::PartStart(head) => {
+					names.push(head.name().unwrap().to_string());
+					bodies.push(Vec::new());
+				}
+				MultipartEvent::Data(data) => bodies.last_mut().unwrap().extend(data),
+				MultipartEvent::PartEnd => {}
+			}
+		}
+
+		assert_eq!(names, vec!["field", "file"]);
+		assert_eq!(bodies[0], b"value");
+		assert_eq!(bodies[1], b"file contents with \r\n--xy not quite a delimiter");
+	}
+}
+
+#[test]
+fn test_multipart_stream_unclosed() {
+	let mut stream = MultipartStream::new("xyz").unwrap();
+	stream.push(b"--xyz\r\n\r\nvalue").unwrap();
+	assert_eq!(stream.finish(), Err(ParseError::MissingClosingBoundary));
+}
+
+#[test]
+fn test_multipart_stream_boundary() {
+	assert_eq!(MultipartStream::new("").err(), Some(ParseError::InvalidBoundary));
+	assert_eq!(MultipartStream::new("x\"y").err(), Some(ParseError::InvalidBoundary));
+}
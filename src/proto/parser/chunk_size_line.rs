@@ -0,0 +1,96 @@
+use crate::proto::parser::ParseError;
+use crate::proto::parser::ParseError::InvalidChunkSize;
+use crate::proto::parser::is_tchar;
+
+fn skip_bws(line: &[u8], mut i: usize) -> usize {
+	while matches!(line.get(i), Some(b' ' | b'\t')) {
+		i += 1;
+	}
+	i
+}
+
+/** chunk-size [ chunk-ext ]; extensions are validated and then ignored */
+pub fn parse_chunk_size_line(line: &[u8]) -> Result<usize, ParseError> {
+	let digits_end = line.iter()
+		.position(|c| !c.is_ascii_hexdigit())
+		.unwrap_or(line.len());
+
+	if digits_end == 0 {
+		return Err(InvalidChunkSize);
+	}
+
+	let mut size = 0usize;
+	for c in line[..digits_end].iter().copied() {
+		let digit = (c as char).to_digit(16).unwrap() as usize;
+		size = size
+			.checked_mul(16)
+			.and_then(|s| s.checked_add(digit))
+			.ok_or(InvalidChunkSize)?;
+	}
+
+	let mut i = skip_bws(line, digits_end);
+	while i < line.len() {
+		if line[i] != b';' {
+			return Err(InvalidChunkSize);
+		}
+		i = skip_bws(line, i + 1);
+
+		let name_start = i;
+		while line.get(i).copied().is_some_and(is_tchar) {
+			i += 1;
+		}
+		if name_start == i {
+			return Err(InvalidChunkSize);
+		}
+		i = skip_bws(line, i);
+
+		if line.get(i) == Some(&b'=') {
+			i = skip_bws(line, i + 1);
+			i = skip_ext_value(line, i)?;
+			i = skip_bws(line, i);
+		}
+	}
+
+	Ok(size)
+}
+
+fn skip_ext_value(line: &[u8], mut i: usize) -> Result<usize, ParseError> {
+	if line.get(i) == Some(&b'"') {
+		i += 1;
+		loop {
+			match line.get(i) {
+				None => return Err(InvalidChunkSize),
+				Some(b'"') => return Ok(i + 1),
+				Some(b'\\') => i += 2,
+				Some(_) => i += 1,
+			}
+		}
+	}
+
+	let value_start = i;
+	while line.get(i).copied().is_some_and(is_tchar) {
+		i += 1;
+	}
+	if value_start == i {
+		return Err(InvalidChunkSize);
+	}
+	Ok(i)
+}
+
+#[test]
+fn test_parse_chunk_size_line() {
+	assert_eq!(parse_chunk_size_line(b"0"), Ok(0));
+	assert_eq!(parse_chunk_size_line(b"1a"), Ok(0x1a));
+	assert_eq!(parse_chunk_size_line(b"FF ; name"), Ok(0xff));
+	assert_eq!(parse_chunk_size_line(b"4;a=b;c=\"d;e\""), Ok(4));
+
+	assert_eq!(parse_chunk_size_line(b""), Err(InvalidChunkSize));
+	assert_eq!(parse_chunk_size_line(b"-4"), Err(InvalidChunkSize));
+	assert_eq!(parse_chunk_size_line(b"4 x"), Err(InvalidChunkSize));
+	assert_eq!(parse_chunk_size_line(b"4;"), Err(InvalidChunkSize));
+	assert_eq!(parse_chunk_size_line(b"4;a=\"open"), Err(InvalidChunkSize));
+	assert_eq!(
+		parse_chunk_size_line(b"ffffffffffffffffffff"),
+		Err(InvalidChunkSize)
+	);
+}
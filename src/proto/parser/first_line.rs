@@ -0,0 +1,53 @@
+use std::str::FromStr;
+use crate::config::ParserConfig;
+use crate::consts::Version;
+use crate::proto::parser::ParseError;
+
+fn is_separator(c: u8, config: &ParserConfig) -> bool {
+	match config.allow_whitespace_separators {
+		true => matches!(c, b' ' | b'\t' | b'\x0b' | b'\x0c'),
+		false => c == b' ',
+	}
+}
+
+/// Splits off the first word and its separator; strictly that's a single SP,
+/// leniently any run of whitespace. `None` for the rest if there's no separator.
+pub(super) fn split_word<'a>(line: &'a [u8], config: &ParserConfig) -> (&'a [u8], Option<&'a [u8]>) {
+	let Some(i) = line.iter().position(|c| is_separator(*c, config)) else {
+		return (line, None);
+	};
+
+	let mut rest = &line[i + 1..];
+	if config.allow_whitespace_separators {
+		while let [c, tail @ ..] = rest {
+			if !is_separator(*c, config) {
+				break;
+			}
+			rest = tail;
+		}
+	}
+	(&line[..i], Some(rest))
+}
+
+/** HTTP-name is case-sensitive, unless told otherwise */
+pub(super) fn parse_version(s: &[u8], config: &ParserConfig) -> Result<Version, ParseError> {
+	let s = std::str::from_utf8(s).map_err(|_| ParseError::InvalidVersion)?;
+	match config.allow_lowercase_version {
+		true => Version::from_str(&s.to_ascii_uppercase()),
+		false => Version::from_str(s),
+	}
+}
+
+#[test]
+fn test_split_word() {
+	let strict = ParserConfig::strict();
+	let lenient = ParserConfig::lenient();
+
+	assert_eq!(split_word(b"GET / HTTP/1.1", &strict), (&b"GET"[..], Some(&b"/ HTTP/1.1"[..])));
+	assert_eq!(split_word(b"GET  /", &strict), (&b"GET"[..], Some(&b" /"[..])));
+	assert_eq!(split_word(b"GET \t /", &lenient), (&b"GET"[..], Some(&b"/"[..])));
+	assert_eq!(split_word(b"GET\t/", &strict), (&b"GET\t/"[..], None));
+
+	assert_eq!(parse_version(b"http/1.1", &strict), Err(ParseError::InvalidVersion));
+	assert_eq!(parse_version(b"http/1.1", &lenient), Ok(Version::HTTP_1_1));
+}
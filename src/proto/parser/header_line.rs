@@ -1,5 +1,5 @@
-use crate::proto::parser::ParseError;
-use crate::proto::parser::ParseError::TBD;
+use crate::config::ParserConfig;
+use crate::proto::parser::{is_tchar, ParseError};
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum HeaderLineParseResult<'a> {
@@ -8,7 +8,11 @@ pub enum HeaderLineParseResult<'a> {
 		field_name: &'a [u8],
 		field_value: &'a [u8],
 	},
-	Err(ParseError),
+	/** `index` is the position of the offending byte within the line */
+	Err {
+		kind: ParseError,
+		index: usize,
+	},
 }
 
 enum ParseState {
@@ -18,24 +22,12 @@ enum ParseState {
 	FieldValue,
 }
 
-fn valid_first_byte_of_field_name(c: &u8) -> bool {
-	c.is_ascii_alphanumeric()
-		|| match c {
-		// todo: what are the allowed characters here?
-		b'_' => true,
-		_ => false
-	}
-}
-
-fn valid_nth_byte_of_field_name(c: u8) -> bool {
-	c.is_ascii_alphanumeric()
-		|| match c {
-		b'-' | b'_' => true,
-		_ => false
-	}
+/** field-vchar, SP or HTAB; obs-text included */
+pub(crate) fn valid_field_value_byte(c: u8) -> bool {
+	c == b'\t' || (c >= 0x20 && c != 0x7f)
 }
 
-pub fn parse_header_line(line: &[u8]) -> HeaderLineParseResult {
+pub fn parse_header_line<'a>(line: &'a [u8], config: &ParserConfig) -> HeaderLineParseResult<'a> {
 	use ParseState::*;
 	use HeaderLineParseResult::*;
 
@@ -43,28 +35,26 @@ pub fn parse_header_line(line: &[u8]) -> HeaderLineParseResult {
 		return Empty;
 	}
 
-	if line
-		.get(0)
-		.map(valid_first_byte_of_field_name)
-		!= Some(true) {
-		return Err(TBD);
+	match line[0] {
+		b':' => return Err { kind: ParseError::EmptyFieldName, index: 0 },
+		c if !is_tchar(c) => return Err { kind: ParseError::InvalidTokenChar, index: 0 },
+		_ => {}
 	}
 
 	let mut state = FieldName;
 
 	let mut filed_name_end_index = 0usize;
-	let mut field_value_start_index = 0usize;
+	let mut field_value_start_index = line.len();
 	let mut last_non_ws_index = line.len() - 1;
 
 	for (i, b) in line.iter()
 		.copied().enumerate().skip(1) {
-		if !b.is_ascii() {
-			panic!("Non-ascii character in header line");
-		}
-
 		match state {
 			FieldName => {
 				if b == b' ' {
+					if !config.allow_whitespace_before_colon {
+						return Err { kind: ParseError::WhitespaceBeforeColon, index: i };
+					}
 					state = WhitespaceBeforeColon;
 					filed_name_end_index = i;
 					continue;
@@ -76,46 +66,48 @@ pub fn parse_header_line(line: &[u8]) -> HeaderLineParseResult {
 					continue;
 				}
 
-				if !valid_nth_byte_of_field_name(b) {
-					return Err(TBD);
+				if !is_tchar(b) {
+					return Err { kind: ParseError::InvalidTokenChar, index: i };
 				}
 			}
 			WhitespaceBeforeColon => {
 				if b == b':' {
 					state = WhitespaceAfterColon;
 				} else if b != b' ' {
-					return Err(TBD)
+					return Err { kind: ParseError::MissingColon, index: i };
 				}
 			}
 			WhitespaceAfterColon => {
-				if b.is_ascii_whitespace() {
+				if b == b' ' || b == b'\t' {
 					continue;
+				} else if !valid_field_value_byte(b) {
+					return Err { kind: ParseError::InvalidFieldValue, index: i };
 				} else {
 					state = FieldValue;
 					field_value_start_index = i;
+					last_non_ws_index = i;
 				}
 			}
 			FieldValue => {
-				if !b.is_ascii_whitespace() {
+				if !valid_field_value_byte(b) {
+					return Err { kind: ParseError::InvalidFieldValue, index: i };
+				}
+				if b != b' ' && b != b'\t' {
 					last_non_ws_index = i;
 				}
 			}
 		}
 	}
 
-	let field_name = &line[..filed_name_end_index];
-	let field_value = &line[field_value_start_index..last_non_ws_index + 1];
-
-	let trailing_whitespace = &line[last_non_ws_index + 1..];
-	for c in trailing_whitespace.iter().copied() {
-		if c != b' ' {
-			return Err(TBD);
-		}
+	match state {
+		FieldName | WhitespaceBeforeColon =>
+			return Err { kind: ParseError::MissingColon, index: line.len() },
+		WhitespaceAfterColon => last_non_ws_index = line.len() - 1,
+		FieldValue => {}
 	}
 
-	if field_name.len() == 0 {
-		return Err(TBD);
-	}
+	let field_name = &line[..filed_name_end_index];
+	let field_value = &line[field_value_start_index.min(last_non_ws_index + 1)..last_non_ws_index + 1];
 
 	Ok {
 		field_name,
@@ -130,7 +122,7 @@ fn test_parse_header_line() {
 
 	let healthy_header_line = b"host: unstd.pl";
 	assert_eq!(
-		parse_header_line(healthy_header_line),
+		parse_header_line(healthy_header_line, &ParserConfig::strict()),
 		Ok {
 			field_name: &healthy_header_line[..4],
 			field_value: &healthy_header_line[6..]
@@ -139,23 +131,56 @@ fn test_parse_header_line() {
 
 	let leading_ws = b" host: unstd.pl";
 	assert_eq!(
-		parse_header_line(leading_ws),
-		Err(TBD)
+		parse_header_line(leading_ws, &ParserConfig::strict()),
+		Err { kind: ParseError::InvalidTokenChar, index: 0 }
 	);
 	let no_colon = b"host unstd.pl";
 	assert_eq!(
-		parse_header_line(no_colon),
-		Err(TBD)
+		parse_header_line(no_colon, &ParserConfig::lenient()),
+		Err { kind: ParseError::MissingColon, index: 5 }
+	);
+	assert_eq!(
+		parse_header_line(b"host : unstd.pl", &ParserConfig::strict()),
+		Err { kind: ParseError::WhitespaceBeforeColon, index: 4 }
+	);
+	assert_eq!(
+		parse_header_line(b"host : unstd.pl", &ParserConfig::lenient()),
+		Ok { field_name: b"host", field_value: b"unstd.pl" }
 	);
 	let empty_field_name = b": unstd.pl";
 	assert_eq!(
-		parse_header_line(empty_field_name),
-		Err(TBD)
+		parse_header_line(empty_field_name, &ParserConfig::strict()),
+		Err { kind: ParseError::EmptyFieldName, index: 0 }
+	);
+	// trailing OWS, RFC 9112 5
+	let tab_character_at_eol = b"host: unstd.pl \t";
+	assert_eq!(
+		parse_header_line(tab_character_at_eol, &ParserConfig::strict()),
+		Ok { field_name: b"host", field_value: b"unstd.pl" }
+	);
+	assert_eq!(
+		parse_header_line(b"host", &ParserConfig::strict()),
+		Err { kind: ParseError::MissingColon, index: 4 }
+	);
+	assert_eq!(
+		parse_header_line(b"ho(st): x", &ParserConfig::strict()),
+		Err { kind: ParseError::InvalidTokenChar, index: 2 }
+	);
+	assert_eq!(
+		parse_header_line(b"x-a: b\x00c", &ParserConfig::strict()),
+		Err { kind: ParseError::InvalidFieldValue, index: 6 }
+	);
+	assert_eq!(
+		parse_header_line(b"x-obs: caf\xe9", &ParserConfig::strict()),
+		Ok { field_name: b"x-obs", field_value: b"caf\xe9" }
+	);
+	assert_eq!(
+		parse_header_line(b"x-\xe9: x", &ParserConfig::strict()),
+		Err { kind: ParseError::InvalidTokenChar, index: 2 }
 	);
-	let tab_character_at_eol = b"host: unstd.pl	";
 	assert_eq!(
-		parse_header_line(tab_character_at_eol),
-		Err(TBD)
+		parse_header_line(b"x-empty:", &ParserConfig::strict()),
+		Ok { field_name: b"x-empty", field_value: b"" }
 	);
 }
 
@@ -165,7 +190,7 @@ fn test_samples() {
 	assert_eq!(&header_str[..13], b"Cache-Control");
 	assert_eq!(&header_str[15..], b"max-age=0");
 	assert_eq!(
-		parse_header_line(header_str),
+		parse_header_line(header_str, &ParserConfig::strict()),
 		HeaderLineParseResult::Ok {
 			field_name: &header_str[..13],
 			field_value: &header_str[15..],
@@ -176,7 +201,7 @@ fn test_samples() {
 	assert_eq!(&header_str[..3], b"DNT");
 	assert_eq!(&header_str[5..6], b"1");
 	assert_eq!(
-		parse_header_line(header_str),
+		parse_header_line(header_str, &ParserConfig::strict()),
 		HeaderLineParseResult::Ok{
 			field_name: &header_str[..3],
 			field_value: &header_str[5..6],
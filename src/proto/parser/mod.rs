@@ -1,10 +1,15 @@
 mod parse_error;
-pub use parse_error::ParseError;
+pub use parse_error::{CollectError, ParseError};
 
+mod first_line;
 mod request_first_line;
 mod header_line;
 mod response_first_line;
+mod chunk_size_line;
+mod token;
 
 pub use request_first_line::*;
 pub use response_first_line::*;
 pub use header_line::*;
+pub use chunk_size_line::*;
+pub use token::*;
@@ -1,8 +1,157 @@
+use std::fmt::{Display, Formatter};
+use crate::consts::StatusCode;
+
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum ParseError {
-	TBD,
-	FirstLine,
-	HeaderLine,
+	/** the first line doesn't have the expected number of parts */
+	InvalidFirstLine,
+	InvalidMethod,
 	InvalidStatusCode,
 	InvalidVersion,
+	/** a byte outside of tchar where a token was expected, e.g. in a field name */
+	InvalidTokenChar,
+	MissingColon,
+	WhitespaceBeforeColon,
+	/** a header line starting with whitespace, continuing the previous one */
+	ObsFold,
+	EmptyFieldName,
+	/** a control character in a field value */
+	InvalidFieldValue,
+	/** CR not followed by LF */
+	BareCR,
+	/** LF not preceded by CR */
+	BareLF,
+	FirstLineTooLong,
+	/** a header or trailer line */
+	HeaderLineTooLong,
+	TooManyHeaders,
+	PreambleTooLarge,
+	BodyTooLarge,
+	TooManyInterimResponses,
+	InvalidContentLength,
+	ConflictingContentLength,
+	/** both Content-Length and Transfer-Encoding */
+	ConflictingFraming,
+	UnsupportedTransferEncoding,
+	/** a Transfer-Encoding with `chunked` anywhere but last */
+	ChunkedNotFinal,
+	/** HTTP/1.0 has no Transfer-Encoding, the framing is faulty */
+	TransferEncodingInHttp10,
+	InvalidChunkSize,
+	ChunkLineTooLong,
+	InvalidChunkDelimiter,
+	IncompleteMessage,
+	InvalidUrl,
+	InvalidMediaType,
+	NotMultipart,
+	MissingBoundary,
+	/** not 1 to 70 of the characters RFC 2046 allows in a boundary */
+	InvalidBoundary,
+	MissingClosingBoundary,
+	InvalidMultipart,
+}
+
+impl Display for ParseError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		use ParseError::*;
+		f.write_str(match self {
+			InvalidFirstLine => "malformed start line",
+			InvalidMethod => "invalid method",
+			InvalidStatusCode => "invalid status code",
+			InvalidVersion => "invalid or unsupported HTTP version",
+			InvalidTokenChar => "invalid character in token",
+			MissingColon => "header line without a colon",
+			WhitespaceBeforeColon => "whitespace between field name and colon",
+			ObsFold => "obsolete line folding",
+			EmptyFieldName => "empty field name",
+			InvalidFieldValue => "invalid character in field value",
+			BareCR => "CR not followed by LF",
+			BareLF => "line ended by LF without CR",
+			FirstLineTooLong => "first line too long",
+			HeaderLineTooLong => "header line too long",
+			TooManyHeaders => "too many header fields",
+			PreambleTooLarge => "header section too large",
+			BodyTooLarge => "body too large",
+			TooManyInterimResponses => "too many interim responses",
+			InvalidContentLength => "invalid Content-Length",
+			ConflictingContentLength => "conflicting Content-Length values",
+			ConflictingFraming => "both Content-Length and Transfer-Encoding",
+			UnsupportedTransferEncoding => "unsupported Transfer-Encoding",
+			ChunkedNotFinal => "chunked is not the final transfer coding",
+			TransferEncodingInHttp10 => "Transfer-Encoding in an HTTP/1.0 message",
+			InvalidChunkSize => "invalid chunk size line",
+			ChunkLineTooLong => "chunk size line too long",
+			InvalidChunkDelimiter => "chunk data not followed by CRLF",
+			IncompleteMessage => "connection closed before the message was complete",
+			InvalidUrl => "invalid URL",
+			InvalidMediaType => "invalid media type",
+			NotMultipart => "not a multipart media type",
+			MissingBoundary => "multipart media type without a boundary",
+			InvalidBoundary => "invalid multipart boundary",
+			MissingClosingBoundary => "multipart body without the closing boundary",
+			InvalidMultipart => "malformed multipart body",
+		})
+	}
+}
+
+impl ParseError {
+	/** What a server should reply to a request rejected with this */
+	pub fn status_code(&self) -> StatusCode {
+		use ParseError::*;
+		match self {
+			FirstLineTooLong => StatusCode::URI_TOO_LONG,
+			HeaderLineTooLong | TooManyHeaders | PreambleTooLarge =>
+				StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE,
+			BodyTooLarge => StatusCode::CONTENT_TOO_LARGE,
+			InvalidVersion => StatusCode::HTTP_VERSION_NOT_SUPPORTED,
+			UnsupportedTransferEncoding => StatusCode::NOT_IMPLEMENTED,
+			_ => StatusCode::BAD_REQUEST,
+		}
+	}
+}
+
+impl std::error::Error for ParseError {}
+
+/** Why a collector rejected a message and where; `offset` counts bytes from the start of the stream */
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct CollectError {
+	kind: ParseError,
+	offset: usize,
+}
+
+impl CollectError {
+	pub fn new(kind: ParseError, offset: usize) -> Self {
+		Self {
+			kind,
+			offset,
+		}
+	}
+
+	pub fn kind(&self) -> ParseError {
+		self.kind
+	}
+
+	/** Position of the offending byte, or of the start of the offending line or element */
+	pub fn offset(&self) -> usize {
+		self.offset
+	}
+}
+
+impl Display for CollectError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} at byte {}", self.kind, self.offset)
+	}
+}
+
+impl std::error::Error for CollectError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		Some(&self.kind)
+	}
+}
+
+#[test]
+fn test_collect_error_display() {
+	let e = CollectError::new(ParseError::BareCR, 42);
+	assert_eq!(e.to_string(), "CR not followed by LF at byte 42");
+	assert_eq!(e.kind(), ParseError::BareCR);
 }
@@ -1,60 +1,85 @@
 use std::str::FromStr;
+use crate::config::ParserConfig;
 use crate::consts::{Method, Version};
 use crate::proto::parser::ParseError;
-use crate::proto::parser::ParseError::{FirstLine, TBD};
+use crate::proto::parser::ParseError::InvalidFirstLine;
+use super::first_line::{parse_version, split_word};
 
-pub struct RequestFirstLine {
+pub struct RequestFirstLine<'a> {
 	pub method: Method,
-	// todo: change to slice of line
-	pub url_slice: Vec<u8>,
+	pub url_slice: &'a [u8],
 	pub version: Version,
 }
 
-// todo: FIXMEPLS
-pub fn parse_request_first_line(line: &[u8]) -> Result<RequestFirstLine, ParseError> {
-	assert_eq!(line.ends_with(b"\n"), false);
-
-	// todo: "first draft"; fixmepls
-
-	let s = String::from_utf8_lossy(line);
-	let mut it = s.split_whitespace();
-
-	let method;
-	let url_slice;
-	let version;
-
-	match it.next() {
-		None => return Err(FirstLine),
-		Some(method_str) => {
-			method = match Method::from_str(method_str) {
-				Ok(v) => v,
-				Err(e) => return Err(FirstLine)
-			};
-
-			match it.next() {
-				None => return Err(FirstLine),
-				Some(url_str) => {
-					url_slice = url_str.as_bytes().to_owned();
-
-					match it.next() {
-						None => return Err(FirstLine),
-						Some(version_str) => {
-							version = match Version::from_str(version_str) {
-								Ok(v) => v,
-								Err(e) => return Err(FirstLine)
-							};
-						}
-					}
-
-				}
-			}
-		}
+/** `method SP request-target SP HTTP-version`, RFC 9112 3 */
+pub fn parse_request_first_line<'a>(line: &'a [u8], config: &ParserConfig)
+									-> Result<RequestFirstLine<'a>, ParseError> {
+	let (method, rest) = split_word(line, config);
+	let method = std::str::from_utf8(method)
+		.map_err(|_| ParseError::InvalidMethod)
+		.and_then(Method::from_str)?;
+
+	let (url_slice, rest) = split_word(rest.ok_or(InvalidFirstLine)?, config);
+	if url_slice.is_empty() {
+		return Err(InvalidFirstLine);
 	}
 
+	let version = match split_word(rest.ok_or(InvalidFirstLine)?, config) {
+		(version, None) => version,
+		// trailing whitespace is only tolerated along with the other leniencies
+		(version, Some(b"")) if config.allow_whitespace_separators => version,
+		_ => return Err(InvalidFirstLine),
+	};
+
 	Ok(RequestFirstLine {
 		method,
 		url_slice,
-		version,
+		version: parse_version(version, config)?,
 	})
+}
+
+#[test]
+fn test_parse_request_first_line() {
+	let strict = ParserConfig::strict();
+	let lenient = ParserConfig::lenient();
+
+	let v = parse_request_first_line(b"OPTIONS * HTTP/1.1", &strict).unwrap();
+	assert_eq!(v.method, Method::OPTIONS);
+	assert_eq!(v.url_slice, b"*");
+
+	let v = parse_request_first_line(b"PROPFIND /dav/ HTTP/1.1", &strict).unwrap();
+	assert_eq!(v.method, Method::Extension("PROPFIND".to_string()));
+
+	assert_eq!(
+		parse_request_first_line(b"G@T / HTTP/1.1", &strict).err(),
+		Some(ParseError::InvalidMethod)
+	);
+	assert_eq!(
+		parse_request_first_line(b"G\xc3\x89T / HTTP/1.1", &strict).err(),
+		Some(ParseError::InvalidMethod)
+	);
+	assert_eq!(
+		parse_request_first_line(b"GET / HTTP/1.7", &strict).err(),
+		Some(ParseError::InvalidVersion)
+	);
+	assert_eq!(
+		parse_request_first_line(b"GET /", &strict).err(),
+		Some(InvalidFirstLine)
+	);
+
+	for line in [&b"GET  / HTTP/1.1"[..], b"GET / HTTP/1.1 ", b"GET /a b HTTP/1.1"] {
+		assert_eq!(parse_request_first_line(line, &strict).err(), Some(InvalidFirstLine));
+	}
+	assert_eq!(
+		parse_request_first_line(b"GET\t/ HTTP/1.1", &strict).err(),
+		Some(ParseError::InvalidMethod)
+	);
+	assert_eq!(
+		parse_request_first_line(b"GET / http/1.1", &strict).err(),
+		Some(ParseError::InvalidVersion)
+	);
 
+	let v = parse_request_first_line(b"GET \t/index.html  http/1.1 ", &lenient).unwrap();
+	assert_eq!(v.url_slice, b"/index.html");
+	assert_eq!(v.version, Version::HTTP_1_1);
 }
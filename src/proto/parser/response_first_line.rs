@@ -1,8 +1,8 @@
-use std::num::ParseIntError;
-use std::str::FromStr;
+use crate::config::ParserConfig;
 use crate::consts::{StatusCode, Version};
 use crate::proto::parser::ParseError;
-use crate::proto::parser::ParseError::FirstLine;
+use crate::proto::parser::ParseError::InvalidFirstLine;
+use super::first_line::{parse_version, split_word};
 
 pub struct ResponseFirstLine {
 	pub version: Version,
@@ -10,45 +10,78 @@ pub struct ResponseFirstLine {
 	pub status_desc: String,
 }
 
-// todo: ALSO FIXMEPLS
-pub fn parse_response_first_line(line: &[u8])
+/** `HTTP-version SP status-code SP [ reason-phrase ]`, RFC 9112 4 */
+pub fn parse_response_first_line(line: &[u8], config: &ParserConfig)
 								 -> Result<ResponseFirstLine, ParseError> {
+	let (version, rest) = split_word(line, config);
+	let version = parse_version(version, config)?;
 
-	// todo: this is terrible
-
-	let s = String::from_utf8_lossy(line);
-	let mut it = s.split_whitespace();
-
-	let version;
-	let status_code;
-	let status_desc;
-
-	match it.next() {
-		None => return Err(FirstLine),
-		Some(v) => {
-			version = Version::from_str(v)?;
-
-			match it.next() {
-				None => return Err(FirstLine),
-				Some(v) => {
-					status_code = StatusCode::try_from(
-						match v.parse::<u32>() {
-							Ok(code) => code,
-							Err(_e) => return Err(ParseError::InvalidStatusCode),
-						}
-					)?;
-
-					status_desc = it
-						.map(|s| format!("{s} "))
-						.collect::<String>();
-				}
-			}
-		}
+	let (status_code, reason) = split_word(rest.ok_or(InvalidFirstLine)?, config);
+	if status_code.is_empty() {
+		return Err(InvalidFirstLine);
+	}
+	if status_code.len() != 3 || !status_code.iter().all(u8::is_ascii_digit) {
+		return Err(ParseError::InvalidStatusCode);
+	}
+	let status_code = StatusCode::try_from(
+		status_code.iter().fold(0u32, |code, d| code * 10 + (d - b'0') as u32)
+	)?;
+
+	// the SP before an empty reason phrase is still required
+	let reason = match reason {
+		Some(reason) => reason,
+		None if config.allow_missing_reason_phrase => b"",
+		None => return Err(InvalidFirstLine),
+	};
+	if reason.iter().any(|c| *c != b'\t' && (*c < 0x20 || *c == 0x7f)) {
+		return Err(InvalidFirstLine);
 	}
 
 	Ok(ResponseFirstLine {
 		version,
 		status_code,
-		status_desc,
+		// the reason phrase is kept as sent, inner whitespace included
+		status_desc: String::from_utf8_lossy(reason).to_string(),
 	})
 }
+
+#[test]
+fn test_parse_response_first_line() {
+	let strict = ParserConfig::strict();
+	let lenient = ParserConfig::lenient();
+
+	let v = parse_response_first_line(b"HTTP/1.1 301 Moved  For Good", &strict).unwrap();
+	assert_eq!(v.version, Version::HTTP_1_1);
+	assert_eq!(v.status_code, StatusCode::MOVED_PERMANENTLY);
+	assert_eq!(v.status_desc, "Moved  For Good");
+
+	let v = parse_response_first_line(b"HTTP/1.1 299", &lenient).unwrap();
+	assert_eq!(v.status_code.as_u16(), 299);
+	assert_eq!(v.status_desc, "");
+	assert_eq!(
+		parse_response_first_line(b"HTTP/1.1 299", &strict).err(),
+		Some(InvalidFirstLine)
+	);
+	assert_eq!(parse_response_first_line(b"HTTP/1.1 204 ", &strict).unwrap().status_desc, "");
+
+	assert_eq!(
+		parse_response_first_line(b"HTTP/1.1 20 OK", &strict).err(),
+		Some(ParseError::InvalidStatusCode)
+	);
+	assert_eq!(
+		parse_response_first_line(b"HTTP/1.1", &strict).err(),
+		Some(InvalidFirstLine)
+	);
+	assert_eq!(
+		parse_response_first_line(b"HTTP/1.1  200 OK", &strict).err(),
+		Some(InvalidFirstLine)
+	);
+
+	let v = parse_response_first_line(b"http/1.0\t200\t OK", &lenient).unwrap();
+	assert_eq!(v.version, Version::HTTP_1_0);
+	assert_eq!(v.status_desc, "OK");
+	assert_eq!(
+		parse_response_first_line(b"http/1.0 200 OK", &strict).err(),
+		Some(ParseError::InvalidVersion)
+	);
+}
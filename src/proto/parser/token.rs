@@ -0,0 +1,10 @@
+/** tchar, see RFC 9110 5.6.2 */
+pub fn is_tchar(c: u8) -> bool {
+	c.is_ascii_alphanumeric()
+		|| matches!(c, b'!' | b'#' | b'$' | b'%' | b'&' | b'\'' | b'*' | b'+'
+			| b'-' | b'.' | b'^' | b'_' | b'`' | b'|' | b'~')
+}
+
+pub fn is_token(s: &[u8]) -> bool {
+	!s.is_empty() && s.iter().copied().all(is_tchar)
+}
@@ -1,16 +1,62 @@
 use crate::proto::consts::Method;
-use crate::proto::message::Message;
+use crate::proto::message::{BodyRule, BuildError, Message};
 
 mod request_collector;
 mod request_builder;
 
+use crate::consts::Version;
+use crate::url::Url;
 pub use request_collector::RequestCollector as Collector;
 pub use request_builder::RequestBuilder as Builder;
 
 #[derive(Debug)]
 pub struct MessageRequest {
-	/* todo: pub is temporary */
-	pub method: Method,
-	pub url: String,
-	pub message: Message,
+	method: Method,
+	url: Url,
+	message: Message,
+}
+
+impl MessageRequest {
+	pub fn method(&self) -> &Method {
+		&self.method
+	}
+	pub fn url(&self) -> &Url {
+		&self.url
+	}
+	pub fn version(&self) -> Version {
+		self.message.version()
+	}
+	pub fn message(&self) -> &Message {
+		&self.message
+	}
+}
+
+impl MessageRequest {
+	pub fn into_bytes(self) -> Result<Vec<u8>, BuildError> {
+		let mut ret = Vec::new();
+
+		let target = match self.method {
+			Method::CONNECT => self.url.authority().ok_or(BuildError::MissingAuthority)?,
+			_ => self.url.request_target(),
+		};
+		// the fields of a `Url` are pub, nothing so far kept them within a request line
+		if !target.bytes().all(|c| c.is_ascii_graphic()) {
+			return Err(BuildError::InvalidRequestTarget);
+		}
+
+		let first_line = format!(
+			"{} {} {}\r\n",
+			self.method,
+			target,
+			self.message.version()
+		);
+
+		ret.extend_from_slice(first_line.as_bytes());
+
+		// an unframed request has no body, so an empty one needs no framing
+		ret.extend_from_slice(
+			self.message.into_bytes(BodyRule::EmptyIfUnframed)?.as_slice());
+
+		Ok(ret)
+	}
 }
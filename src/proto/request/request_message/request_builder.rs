@@ -1,3 +1,144 @@
+use std::io::Read;
+use crate::consts::{Method, Version};
+use crate::proto::message::MessageBuilder;
+use crate::multipart::MultipartBody;
+use crate::form_urlencoded::FormBuilder;
+use crate::proto::parser::ParseError;
+use crate::request::Request;
+use crate::url::Url;
+
 pub struct RequestBuilder {
+	method: Method,
+	url: Url,
+	version: Version,
+	message_builder: MessageBuilder,
+}
+
+impl Default for RequestBuilder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl RequestBuilder {
+	pub fn new() -> Self {
+		Self {
+			method: Method::GET,
+			url: Url {
+				path: "/".to_string(),
+				..Url::default()
+			},
+			version: Version::HTTP_1_1,
+			message_builder: Default::default(),
+		}
+	}
+
+	pub fn into_request(self) -> Request {
+		Request {
+			method: self.method,
+			url: self.url,
+			message: self.message_builder.into_message(self.version),
+		}
+	}
+}
+
+impl RequestBuilder {
+	pub fn set_method(&mut self, method: Method) -> &mut Self {
+		self.method = method;
+		self
+	}
+	pub fn set_url(&mut self, url: Url) -> &mut Self {
+		self.url = url;
+		self
+	}
+	/** Parsed as a request-target for the method set so far */
+	pub fn set_target(&mut self, target: &str) -> Result<&mut Self, ParseError> {
+		self.url = Url::from_request_target(target.as_bytes(), &self.method)?;
+		Ok(self)
+	}
+	pub fn set_version(&mut self, version: Version) -> &mut Self {
+		self.version = version;
+		self
+	}
+	pub fn push_header(&mut self, k: &str, v: &str) -> &mut Self {
+		self.message_builder.push_header(k, v);
+		self
+	}
+	pub fn set_header(&mut self, k: &str, v: &str) -> &mut Self {
+		self.message_builder.set_header(k, v);
+		self
+	}
+	pub fn set_body(&mut self, body: &[u8]) -> &mut Self {
+		self.message_builder.set_body(body);
+		self
+	}
+	pub fn append_body(&mut self, body: &[u8]) -> &mut Self {
+		self.message_builder.append_body(body);
+		self
+	}
+	pub fn set_multipart(&mut self, multipart: MultipartBody) -> &mut Self {
+		self.message_builder.set_multipart(multipart);
+		self
+	}
+	pub fn set_form(&mut self, form: &FormBuilder) -> &mut Self {
+		self.message_builder.set_form(form);
+		self
+	}
+	pub fn body_from_reader<R: Read + 'static>(&mut self, reader: R) -> &mut Self {
+		self.message_builder.body_from_reader(reader);
+		self
+	}
+}
+
+#[test]
+fn test_request_into_bytes() {
+	use crate::proto::message::BuildError;
+	use crate::url::Host;
+
+	let mut builder = RequestBuilder::new();
+	builder
+		.set_method(Method::POST)
+		.set_target("/api/get-list?hello=world").unwrap()
+		.push_header("host", "localhost")
+		.set_body(b"Lorem ipsum");
+
+	assert_eq!(
+		builder.into_request().into_bytes().unwrap(),
+		b"POST /api/get-list?hello=world HTTP/1.1\r\n\
+			host: localhost\r\n\
+			Content-Length: 11\r\n\r\n\
+			Lorem ipsum"
+	);
+	let mut builder = RequestBuilder::new();
+	builder.set_method(Method::CONNECT);
+	assert_eq!(builder.into_request().into_bytes(), Err(BuildError::MissingAuthority));
+
+	let mut builder = RequestBuilder::new();
+	builder
+		.set_method(Method::CONNECT)
+		.set_url(Url::parse_authority("example.com:443").unwrap());
+	assert_eq!(
+		builder.into_request().into_bytes().unwrap(),
+		b"CONNECT example.com:443 HTTP/1.1\r\n\r\n"
+	);
+
+	let url = Url {
+		path: "/a HTTP/1.1\r\nX: y\r\n\r\nGET /b".to_string(),
+		..Url::default()
+	};
+	let mut builder = RequestBuilder::new();
+	builder.set_url(url);
+	assert_eq!(builder.into_request().into_bytes(), Err(BuildError::InvalidRequestTarget));
+
+	let mut url = Url::parse("/search").unwrap();
+	url.query_string = Some("q=a b".to_string());
+	let mut builder = RequestBuilder::new();
+	builder.set_url(url);
+	assert_eq!(builder.into_request().into_bytes(), Err(BuildError::InvalidRequestTarget));
 
+	let mut url = Url::parse_authority("example.com:443").unwrap();
+	url.host = Some(Host::Domain("example.com\r\nX: y".to_string()));
+	let mut builder = RequestBuilder::new();
+	builder.set_method(Method::CONNECT).set_url(url);
+	assert_eq!(builder.into_request().into_bytes(), Err(BuildError::InvalidRequestTarget));
 }
@@ -1,15 +1,16 @@
+use crate::config::{CollectorLimits, ParserConfig};
 use crate::consts::{Method, Version};
-use crate::proto::buffer_reader::{DelayedConsumeResult, DelayedStateBuffer};
-use crate::proto::message::{ CollectResult, CollectorState, MessageCollector, MessageCollectorAdvance};
+use crate::proto::message::{BodyRule, CollectResult, MessageCollector, MessageCollectorAdvance};
 use crate::proto::parser;
-use crate::proto::parser::ParseError;
+use crate::proto::parser::{CollectError, ParseError};
 use crate::request::Request;
+use crate::url::Url;
 
 pub struct RequestCollector {
 	collect_result: CollectResult,
 
 	method: Option<Method>,
-	url: Option<String>,
+	url: Option<Url>,
 	version: Option<Version>,
 
 	message_collector: MessageCollector,
@@ -17,6 +18,12 @@ pub struct RequestCollector {
 	internal_buffer: Vec<u8>,
 }
 
+impl Default for RequestCollector {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
 impl RequestCollector {
 	pub fn new() -> Self {
 		Self {
@@ -31,13 +38,60 @@ impl RequestCollector {
 		}
 	}
 
+	/** To be set before any bytes are pushed */
+	pub fn with_limits(self, limits: CollectorLimits) -> Self {
+		let mut message_collector = MessageCollector::with_limits(limits);
+		message_collector.set_config(self.message_collector.config());
+		Self {
+			message_collector,
+			..self
+		}
+	}
+
+	/** Strict by default, see [`ParserConfig`]; to be set before any bytes are pushed */
+	pub fn with_config(mut self, config: ParserConfig) -> Self {
+		self.message_collector.set_config(config);
+		self
+	}
+
 	pub fn is_finished(&self) -> bool {
 		self.collect_result.is_some()
 	}
 
-	pub fn into_request(self) -> Result<Request, ParseError> {
+	/** Faulty framing was let through by the config, nothing may follow this request */
+	pub fn must_close(&self) -> bool {
+		self.message_collector.must_close()
+	}
+
+	/// Takes the body bytes collected so far, e.g. to stream a large upload
+	/// elsewhere; the finished request only holds what wasn't taken.
+	pub fn take_body(&mut self) -> Vec<u8> {
+		self.message_collector.take_body()
+	}
+
+	/// Collector for the request that follows this one on the connection;
+	/// limits, config and error offsets carry over, and so do the bytes pushed
+	/// past the end of this one, by move, to be parsed on the next push.
+	pub(crate) fn next_collector(&mut self) -> Self {
+		Self {
+			message_collector: self.message_collector.next_message(),
+			internal_buffer: std::mem::take(&mut self.internal_buffer),
+			..Self::new()
+		}
+	}
+
+	/// The finished request and the bytes pushed past its end, i.e. the start
+	/// of a pipelined request, to be pushed to the next collector.
+	pub fn finish(mut self) -> Result<(Request, Vec<u8>), CollectError> {
+		self.message_collector.compact(&mut self.internal_buffer);
+		let remaining = std::mem::take(&mut self.internal_buffer);
+		self.into_request().map(|request| (request, remaining))
+	}
+
+	pub fn into_request(self) -> Result<Request, CollectError> {
 		match self.collect_result {
-			None => panic!("Attempted to convert an incomplete request"),
+			None => Err(CollectError::new(
+				ParseError::IncompleteMessage, self.message_collector.stream_position())),
 			Some(Err(e)) => Err(e),
 			Some(Ok(())) => Ok(Request {
 				method: self.method.unwrap(),
@@ -49,36 +103,41 @@ impl RequestCollector {
 }
 
 impl RequestCollector {
+	/** Returns how many of `bytes` were taken; none once the collector is finished */
 	pub fn push_bytes(&mut self, bytes: &[u8]) -> usize {
 		if self.is_finished() {
-			panic!("Attempted to push bytes on a finished collector");
+			return 0;
 		}
 		self.internal_buffer.extend_from_slice(bytes);
 
+		let config = self.message_collector.config();
 		match self.message_collector.advance(
 			self.internal_buffer.as_slice(),
 			|s| {
-				let v = parser::parse_request_first_line(s)?;
+				let v = parser::parse_request_first_line(s, &config)?;
 
+				self.url = Some(Url::from_request_target(v.url_slice, &v.method)?);
 				self.method = Some(v.method);
-				self.url = Some(String::from_utf8_lossy(
-					v.url_slice.as_slice()).to_string());
 				self.version = Some(v.version);
 
-				Ok(())
+				// requests never run until the connection closes
+				Ok((v.version, BodyRule::EmptyIfUnframed))
 			}
 		) {
-			MessageCollectorAdvance::NeedMoreBytes => bytes.len(),
+			MessageCollectorAdvance::NeedMoreBytes => {
+				self.message_collector.compact(&mut self.internal_buffer);
+				bytes.len()
+			}
 			MessageCollectorAdvance::Finished { remaining_bytes } => {
 				self.collect_result = Some(Ok(()));
-				dbg!(bytes.len(), remaining_bytes);
-				bytes.len() - remaining_bytes
+				// left in the buffer for `finish` or the next collector; a buffer handed
+				// over by the previous collector may hold more than `bytes`
+				bytes.len().saturating_sub(remaining_bytes)
 			}
 			MessageCollectorAdvance::Error(e) => {
 				self.collect_result = Some(Err(e));
 				0
 			}
 		}
-
 	}
 }
@@ -1,5 +1,5 @@
-use crate::proto::consts::StatusCode;
-use crate::proto::message::Message;
+use crate::proto::consts::{Method, StatusCode};
+use crate::proto::message::{BodyRule, BuildError, Message};
 
 mod response_collector;
 mod response_builder;
@@ -16,20 +16,118 @@ pub struct MessageResponse {
 }
 
 impl MessageResponse {
-	pub fn into_bytes(self) -> Vec<u8> {
+	pub fn status_code(&self) -> StatusCode {
+		self.status_code
+	}
+	pub fn status_desc(&self) -> &str {
+		self.status_desc.as_str()
+	}
+	pub fn version(&self) -> Version {
+		self.message.version()
+	}
+	pub fn message(&self) -> &Message {
+		&self.message
+	}
+}
+
+impl MessageResponse {
+	/** As a response to a request of any method but HEAD */
+	pub fn into_bytes(self) -> Result<Vec<u8>, BuildError> {
+		self.serialize(false)
+	}
+
+	/** A response to HEAD may declare the Content-Length of a GET, without the body */
+	pub fn into_bytes_for(self, method: &Method) -> Result<Vec<u8>, BuildError> {
+		self.serialize(*method == Method::HEAD)
+	}
+
+	fn serialize(self, to_head: bool) -> Result<Vec<u8>, BuildError> {
 		let mut ret = Vec::new();
 
+		// the reason phrase we were given wins over the canonical one
+		let status_desc = if self.status_desc.is_empty() {
+			self.status_code.as_desc().unwrap_or_default()
+		} else {
+			self.status_desc.as_str()
+		};
+		// reason-phrase, RFC 9112 4; a CRLF in there would end the status line early
+		if !status_desc.bytes().all(|c| c == b'\t' || c == b' ' || c.is_ascii_graphic() || c >= 0x80) {
+			return Err(BuildError::InvalidReasonPhrase);
+		}
+
 		let first_line = format!(
 			"{} {} {}\r\n",
 			self.message.version(),
-			self.status_code as usize,
-			self.status_code.as_desc()
+			self.status_code,
+			status_desc
 		);
 
 		ret.extend_from_slice(first_line.as_bytes());
 
-		ret.extend_from_slice(self.message.into_bytes().as_slice());
+		let headers = self.message.headers();
+		let body_rule = match self.status_code.as_u16() {
+			100..=199 | 204 if headers.contains("content-length") || headers.contains("transfer-encoding") =>
+				return Err(BuildError::FramingNotAllowed),
+			// what the headers declare is that of the response to a GET
+			100..=199 | 204 | 304 => BodyRule::NoBody,
+			_ if to_head => BodyRule::NoBody,
+			_ => BodyRule::UntilCloseIfUnframed,
+		};
+
+		ret.extend_from_slice(self.message.into_bytes(body_rule)?.as_slice());
+
+		Ok(ret)
+	}
+}
+
+#[test]
+fn test_reason_phrase() {
+	let mut builder = Builder::new();
+	builder.set_status(StatusCode::OK).set_status_desc("OK\r\nSet-Cookie: x=1");
+	assert_eq!(builder.into_response().into_bytes(), Err(BuildError::InvalidReasonPhrase));
+
+	let mut builder = Builder::new();
+	builder.set_status(StatusCode::OK).set_status_desc("Tout va bien\t\u{e9}");
+	assert_eq!(
+		builder.into_response().into_bytes().unwrap(),
+		"HTTP/1.1 200 Tout va bien\t\u{e9}\r\nContent-Length: 0\r\n\r\n".as_bytes()
+	);
+}
+
+#[test]
+fn test_bodiless_framing() {
+	let mut builder = Builder::new();
+	builder.set_status(StatusCode::OK).set_header("Content-Length", "5");
+	assert_eq!(
+		builder.into_response().into_bytes(),
+		Err(BuildError::ContentLengthMismatch { declared: 5, actual: 0 })
+	);
+
+	let mut builder = Builder::new();
+	builder.set_status(StatusCode::OK).set_header("Content-Length", "5");
+	assert_eq!(
+		builder.into_response().into_bytes_for(&Method::HEAD).unwrap(),
+		b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n"
+	);
+
+	let mut builder = Builder::new();
+	builder.set_status(StatusCode::OK).set_body(b"hello");
+	assert_eq!(builder.into_response().into_bytes_for(&Method::HEAD), Err(BuildError::BodyNotAllowed));
+
+	let mut builder = Builder::new();
+	builder.set_status(StatusCode::NOT_MODIFIED).set_header("Content-Length", "5");
+	assert_eq!(
+		builder.into_response().into_bytes_for(&Method::GET).unwrap(),
+		b"HTTP/1.1 304 Not Modified\r\nContent-Length: 5\r\n\r\n"
+	);
 
-		ret
+	for (status, header) in [
+		(StatusCode::NO_CONTENT, "Content-Length"),
+		(StatusCode::NO_CONTENT, "Transfer-Encoding"),
+		(StatusCode::CONTINUE, "Content-Length"),
+	] {
+		let mut builder = Builder::new();
+		builder.set_status(status).set_header(header, "0");
+		assert_eq!(builder.into_response().into_bytes(), Err(BuildError::FramingNotAllowed));
 	}
 }
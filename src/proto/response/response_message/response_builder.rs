@@ -1,9 +1,13 @@
+use std::io::Read;
 use crate::consts::{StatusCode, Version};
 use crate::proto::message::MessageBuilder;
+use crate::multipart::MultipartBody;
+use crate::form_urlencoded::FormBuilder;
 use crate::response::Response;
 
 pub struct ResponseBuilder {
 	status_code: StatusCode,
+	status_desc: String,
 	version: Version,
 	message_builder: MessageBuilder,
 }
@@ -12,6 +16,7 @@ impl Default for ResponseBuilder {
 	fn default() -> Self {
 		Self {
 			status_code: StatusCode::SUCCESS,
+			status_desc: String::new(),
 			version: Version::HTTP_1_1,
 			message_builder: Default::default(),
 		}
@@ -22,6 +27,7 @@ impl ResponseBuilder {
 	pub fn new() -> Self{
 		Self {
 			status_code: StatusCode::SUCCESS,
+			status_desc: String::new(),
 			version: Version::HTTP_1_1,
 			message_builder: Default::default(),
 		}
@@ -30,19 +36,53 @@ impl ResponseBuilder {
 	pub fn into_response(self) -> Response {
 		Response {
 			status_code: self.status_code,
-			status_desc: "".to_string(),
+			status_desc: self.status_desc,
 			message: self.message_builder.into_message(self.version),
 		}
 	}
 }
 
 impl ResponseBuilder {
+	/** Also resets the reason phrase to the canonical one of `status` */
 	pub fn set_status(&mut self, status: StatusCode) -> &mut Self {
 		self.status_code = status;
+		self.status_desc.clear();
+		self
+	}
+	pub fn set_status_desc(&mut self, status_desc: &str) -> &mut Self {
+		self.status_desc = status_desc.to_string();
+		self
+	}
+	pub fn set_version(&mut self, version: Version) -> &mut Self {
+		self.version = version;
 		self
 	}
 	pub fn push_header(&mut self, k: &str, v: &str) -> &mut Self {
 		self.message_builder.push_header(k, v);
 		self
 	}
+	pub fn set_header(&mut self, k: &str, v: &str) -> &mut Self {
+		self.message_builder.set_header(k, v);
+		self
+	}
+	pub fn set_body(&mut self, body: &[u8]) -> &mut Self {
+		self.message_builder.set_body(body);
+		self
+	}
+	pub fn append_body(&mut self, body: &[u8]) -> &mut Self {
+		self.message_builder.append_body(body);
+		self
+	}
+	pub fn set_multipart(&mut self, multipart: MultipartBody) -> &mut Self {
+		self.message_builder.set_multipart(multipart);
+		self
+	}
+	pub fn set_form(&mut self, form: &FormBuilder) -> &mut Self {
+		self.message_builder.set_form(form);
+		self
+	}
+	pub fn body_from_reader<R: Read + 'static>(&mut self, reader: R) -> &mut Self {
+		self.message_builder.body_from_reader(reader);
+		self
+	}
 }
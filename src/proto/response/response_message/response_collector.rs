@@ -1,41 +1,105 @@
-use crate::consts::{StatusCode, Version};
-use crate::proto::message::{CollectResult, MessageCollector, MessageCollectorAdvance};
+use crate::config::{CollectorLimits, ParserConfig};
+use crate::consts::{Method, StatusCode, Version};
+use crate::proto::message::{BodyRule, CollectResult, MessageCollector, MessageCollectorAdvance};
 use crate::proto::parser;
-use crate::proto::parser::ParseError;
-use crate::request::Request;
+use crate::proto::parser::{CollectError, ParseError};
 use crate::response::Response;
 
 pub struct ResponseCollector {
 	collect_result: CollectResult,
 
+	request_method: Option<Method>,
+
 	version: Option<Version>,
 	status_code: Option<StatusCode>,
 	status_desc: Option<String>,
 
+	informational: Vec<Response>,
+
 	message_collector: MessageCollector,
 
 	internal_buffer: Vec<u8>,
 }
 
+impl Default for ResponseCollector {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
 impl ResponseCollector {
 	pub fn new() -> Self {
+		// servers out there are sloppier than the clients we accept requests from
+		let mut message_collector = MessageCollector::new();
+		message_collector.set_config(ParserConfig::lenient());
+
 		Self {
 			collect_result: None,
+			request_method: None,
 			version: None,
 			status_code: None,
 			status_desc: None,
-			message_collector: MessageCollector::new(),
+			informational: Vec::new(),
+			message_collector,
 			internal_buffer: Vec::new(),
 		}
 	}
 
+	/** Collector for the response to a request made with `method`, see RFC 9112 6.3 */
+	pub fn for_request(method: &Method) -> Self {
+		Self {
+			request_method: Some(method.clone()),
+			..Self::new()
+		}
+	}
+
+	/** To be set before any bytes are pushed; interim responses share the limits */
+	pub fn with_limits(self, limits: CollectorLimits) -> Self {
+		let mut message_collector = MessageCollector::with_limits(limits);
+		message_collector.set_config(self.message_collector.config());
+		Self {
+			message_collector,
+			..self
+		}
+	}
+
+	/** Lenient by default, see [`ParserConfig`]; to be set before any bytes are pushed */
+	pub fn with_config(mut self, config: ParserConfig) -> Self {
+		self.message_collector.set_config(config);
+		self
+	}
+
 	pub fn is_finished(&self) -> bool {
 		self.collect_result.is_some()
 	}
 
-	pub fn into_response(self) -> Result<Response, ParseError> {
+	/** Faulty framing was let through by the config, nothing may follow this response */
+	pub fn must_close(&self) -> bool {
+		self.message_collector.must_close()
+	}
+
+	/** Interim (1xx) responses received so far, in order */
+	pub fn informational_responses(&self) -> &[Response] {
+		self.informational.as_slice()
+	}
+
+	/// Takes the body bytes collected so far, e.g. to stream a large download
+	/// elsewhere; the finished response only holds what wasn't taken.
+	pub fn take_body(&mut self) -> Vec<u8> {
+		self.message_collector.take_body()
+	}
+
+	/// The finished response and the bytes pushed past its end, i.e. the start
+	/// of the response to a pipelined request.
+	pub fn finish(mut self) -> Result<(Response, Vec<u8>), CollectError> {
+		let remaining = std::mem::take(&mut self.internal_buffer);
+		self.into_response().map(|response| (response, remaining))
+	}
+
+	pub fn into_response(self) -> Result<Response, CollectError> {
 		match self.collect_result {
-			None => panic!("Attempted to convert an incomplete response"),
+			None => Err(CollectError::new(
+				ParseError::IncompleteMessage, self.message_collector.stream_position())),
 			Some(Err(e)) => Err(e),
 			Some(Ok(())) => Ok(Response {
 				status_code: self.status_code.unwrap(),
@@ -46,36 +110,160 @@ impl ResponseCollector {
 	}
 }
 
+fn body_rule(request_method: Option<&Method>, status: u16) -> BodyRule {
+	match (request_method, status) {
+		(Some(Method::HEAD), _) => BodyRule::NoBody,
+		(Some(Method::CONNECT), 200..=299) => BodyRule::NoBody,
+		(_, 100..=199 | 204 | 304) => BodyRule::NoBody,
+		_ => BodyRule::UntilCloseIfUnframed,
+	}
+}
+
 impl ResponseCollector {
+	fn is_interim(&self) -> bool {
+		// 101 Switching Protocols is the last response on this protocol
+		matches!(self.status_code.map(|s| s.as_u16()), Some(100 | 102..=199))
+	}
+
+	/** Stores the finished interim response and starts over on the rest of the buffer */
+	fn next_after_interim(&mut self) {
+		let next_collector = self.message_collector.next_message();
+		let message_collector = std::mem::replace(&mut self.message_collector, next_collector);
+
+		self.informational.push(Response {
+			status_code: self.status_code.take().unwrap(),
+			status_desc: self.status_desc.take().unwrap(),
+			message: message_collector.into_message(self.version.take().unwrap()),
+		});
+	}
+
+	/** Returns how many of `bytes` were taken; none once the collector is finished */
 	pub fn push_bytes(&mut self, bytes: &[u8]) -> usize {
 		if self.is_finished() {
-			panic!("Finished");
+			return 0;
 		}
 		self.internal_buffer.extend_from_slice(bytes);
 
-		match self.message_collector.advance(
-			self.internal_buffer.as_slice(),
-			|s| {
-				let v = parser::parse_response_first_line(s)?;
+		let config = self.message_collector.config();
+		loop {
+			match self.message_collector.advance(
+				self.internal_buffer.as_slice(),
+				|s| {
+					let v = parser::parse_response_first_line(s, &config)?;
 
+					let rule = body_rule(self.request_method.as_ref(), v.status_code.as_u16());
 
-				self.version = Some(v.version);
-				self.status_code = Some(v.status_code);
-				self.status_desc = Some(v.status_desc);
+					self.version = Some(v.version);
+					self.status_code = Some(v.status_code);
+					self.status_desc = Some(v.status_desc);
 
-				Ok(())
-			},
-		) {
-			MessageCollectorAdvance::NeedMoreBytes => bytes.len(),
-			MessageCollectorAdvance::Finished { remaining_bytes } => {
-				self.collect_result = Some(Ok(()));
-				dbg!(bytes.len(), remaining_bytes);
-				bytes.len() - remaining_bytes
-			}
-			MessageCollectorAdvance::Error(e) => {
-				self.collect_result = Some(Err(e));
-				0
+					Ok((v.version, rule))
+				},
+			) {
+				MessageCollectorAdvance::NeedMoreBytes => {
+					self.message_collector.compact(&mut self.internal_buffer);
+					return bytes.len();
+				}
+				MessageCollectorAdvance::Finished { remaining_bytes } => {
+					if self.is_interim() {
+						if self.informational.len() >= self.message_collector.limits().max_interim_responses {
+							let e = CollectError::new(
+								ParseError::TooManyInterimResponses, self.message_collector.message_start());
+							self.collect_result = Some(Err(e));
+							return 0;
+						}
+						self.next_after_interim();
+						continue;
+					}
+
+					self.collect_result = Some(Ok(()));
+					// what's left is past the end of the message, thus all of it from `bytes`
+					self.message_collector.compact(&mut self.internal_buffer);
+					return bytes.len() - remaining_bytes;
+				}
+				MessageCollectorAdvance::Error(e) => {
+					self.collect_result = Some(Err(e));
+					return 0;
+				}
 			}
 		}
 	}
 }
+
+impl ResponseCollector {
+	/// To be called once the peer closed the connection, completes a response
+	/// whose body is delimited by the close; any other incomplete response
+	/// becomes an error.
+	pub fn signal_connection_close(&mut self) {
+		if self.is_finished() {
+			return;
+		}
+
+		self.collect_result = match self.message_collector
+			.signal_connection_close(self.internal_buffer.as_slice()) {
+			MessageCollectorAdvance::NeedMoreBytes => unreachable!(),
+			MessageCollectorAdvance::Finished { .. } => Some(Ok(())),
+			MessageCollectorAdvance::Error(e) => Some(Err(e)),
+		};
+		self.message_collector.compact(&mut self.internal_buffer);
+	}
+}
+
+#[test]
+fn test_response_until_close() {
+	let mut collector = ResponseCollector::new();
+	collector.push_bytes(b"HTTP/1.1 200 OK\r\nServer: test\r\n\r\nall of ");
+	collector.push_bytes(b"this is body");
+	assert!(!collector.is_finished());
+
+	collector.signal_connection_close();
+	assert!(collector.is_finished());
+
+	let response = collector.into_response().unwrap();
+	assert_eq!(response.message.body(), b"all of this is body");
+}
+
+#[test]
+fn test_response_to_head() {
+	let mut collector = ResponseCollector::for_request(&Method::HEAD);
+	collector.push_bytes(b"HTTP/1.1 200 OK\r\nContent-Length: 1234\r\n\r\n");
+	assert!(collector.is_finished());
+	assert!(collector.into_response().unwrap().message.body().is_empty());
+
+	let mut collector = ResponseCollector::for_request(&Method::GET);
+	collector.push_bytes(b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\n");
+	assert!(!collector.is_finished());
+	collector.push_bytes(b"body");
+	assert_eq!(collector.into_response().unwrap().message.body(), b"body");
+}
+
+#[test]
+fn test_interim_and_bodiless_responses() {
+	let mut collector = ResponseCollector::new();
+	collector.push_bytes(b"HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 103 Early Hints\r\n");
+	collector.push_bytes(b"Link: </style.css>\r\n\r\nHTTP/1.1 204 No Content\r\n");
+	collector.push_bytes(b"Content-Length: 10\r\n\r\n");
+	assert!(collector.is_finished());
+
+	let interim: Vec<u16> = collector.informational_responses()
+		.iter()
+		.map(|r| r.status_code.as_u16())
+		.collect();
+	assert_eq!(interim, vec![100, 103]);
+
+	let response = collector.into_response().unwrap();
+	assert_eq!(response.status_code, StatusCode::NO_CONTENT);
+	assert!(response.message.body().is_empty());
+
+	let mut collector = ResponseCollector::new();
+	collector.push_bytes(b"HTTP/1.1 304 Not Modified\r\nContent-Length: 10\r\n\r\n");
+	assert!(collector.is_finished());
+
+	let limits = CollectorLimits { max_interim_responses: 2, ..CollectorLimits::default() };
+	let mut collector = ResponseCollector::new().with_limits(limits);
+	collector.push_bytes(&b"HTTP/1.1 103 Early Hints\r\n\r\n".repeat(3));
+	assert_eq!(
+		collector.into_response().unwrap_err(),
+		CollectError::new(ParseError::TooManyInterimResponses, 56)
+	);
+}
@@ -1,35 +1,619 @@
-#[derive(Default, Clone)]
+use std::fmt::{Display, Formatter};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+use crate::consts::Method;
+use crate::form_urlencoded;
+use crate::proto::parser::ParseError;
+
+pub mod percent_encoding;
+mod path;
+mod url_builder;
+
+pub use path::{remove_dot_segments, safe_join};
+pub use url_builder::UrlBuilder;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Host {
+	Domain(String),
+	Ipv4(Ipv4Addr),
+	Ipv6(Ipv6Addr),
+}
+
+impl Display for Host {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Host::Domain(domain) => write!(f, "{domain}"),
+			Host::Ipv4(addr) => write!(f, "{addr}"),
+			Host::Ipv6(addr) => write!(f, "[{addr}]"),
+		}
+	}
+}
+
+impl FromStr for Host {
+	type Err = ParseError;
+
+	/** Bare IPv6 addresses are accepted without brackets */
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match Ipv6Addr::from_str(s) {
+			Ok(addr) => Ok(Host::Ipv6(addr)),
+			Err(_) => parse_host(s),
+		}
+	}
+}
+
+/** URI or relative reference, see RFC 3986; components are kept percent-encoded */
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
 pub struct Url {
-	// [scheme://][domain][:port]/[path][?query_string][#fragment]
+	// [scheme:][//[userinfo@]host[:port]][path][?query_string][#fragment]
 	pub scheme: Option<String>,
-	pub domain: Option<String>,
+	pub userinfo: Option<String>,
+	pub host: Option<Host>,
 	pub port: Option<u16>,
 	pub path: String,
-	pub query_string: String,
-	pub fragment: String,
+	pub query_string: Option<String>,
+	pub fragment: Option<String>,
+}
+
+fn is_unreserved(c: u8) -> bool {
+	c.is_ascii_alphanumeric() || matches!(c, b'-' | b'.' | b'_' | b'~')
+}
+
+fn is_sub_delim(c: u8) -> bool {
+	matches!(c, b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*' | b'+' | b',' | b';' | b'=')
+}
+
+fn is_pchar(c: u8) -> bool {
+	is_unreserved(c) || is_sub_delim(c) || matches!(c, b':' | b'@')
+}
+
+/** Every byte passes `allowed` or starts a well-formed `%XX` */
+fn validate(s: &str, allowed: impl Fn(u8) -> bool) -> Result<(), ParseError> {
+	let s = s.as_bytes();
+	let mut i = 0;
+	while i < s.len() {
+		if s[i] == b'%' {
+			if !(s.get(i + 1).is_some_and(u8::is_ascii_hexdigit)
+				&& s.get(i + 2).is_some_and(u8::is_ascii_hexdigit)) {
+				return Err(ParseError::InvalidUrl);
+			}
+			i += 3;
+		} else if allowed(s[i]) {
+			i += 1;
+		} else {
+			return Err(ParseError::InvalidUrl);
+		}
+	}
+	Ok(())
+}
+
+fn parse_host(s: &str) -> Result<Host, ParseError> {
+	if let Some(literal) = s.strip_prefix('[') {
+		// IPvFuture and zone identifiers aren't supported
+		return literal.strip_suffix(']')
+			.and_then(|l| Ipv6Addr::from_str(l).ok())
+			.map(Host::Ipv6)
+			.ok_or(ParseError::InvalidUrl);
+	}
+
+	if let Ok(addr) = Ipv4Addr::from_str(s) {
+		return Ok(Host::Ipv4(addr));
+	}
+
+	validate(s, |c| is_unreserved(c) || is_sub_delim(c))?;
+	Ok(Host::Domain(s.to_string()))
+}
+
+/** `[userinfo@]host[:port]` */
+fn parse_authority(s: &str) -> Result<(Option<String>, Host, Option<u16>), ParseError> {
+	let (userinfo, host_port) = match s.rfind('@') {
+		Some(i) => (Some(&s[..i]), &s[i + 1..]),
+		None => (None, s),
+	};
+	if let Some(userinfo) = userinfo {
+		validate(userinfo, |c| is_unreserved(c) || is_sub_delim(c) || c == b':')?;
+	}
+
+	// the last colon outside of an IPv6 literal separates the port
+	let port_sep = match host_port.rfind(']') {
+		Some(bracket) => host_port[bracket..].find(':').map(|i| bracket + i),
+		None => host_port.rfind(':'),
+	};
+	let (host, port) = match port_sep {
+		Some(i) => (&host_port[..i], &host_port[i + 1..]),
+		None => (host_port, ""),
+	};
+
+	let port = match port {
+		"" => None,
+		p if p.bytes().all(|c| c.is_ascii_digit()) =>
+			Some(p.parse::<u16>().map_err(|_| ParseError::InvalidUrl)?),
+		_ => return Err(ParseError::InvalidUrl),
+	};
+
+	Ok((userinfo.map(str::to_string), parse_host(host)?, port))
+}
+
+fn scheme_end(s: &str) -> Option<usize> {
+	let end = s.find(':')?;
+	let scheme = &s.as_bytes()[..end];
+
+	let valid = scheme.first().is_some_and(u8::is_ascii_alphabetic)
+		&& scheme.iter().all(|c| c.is_ascii_alphanumeric() || matches!(c, b'+' | b'-' | b'.'));
+	valid.then_some(end)
 }
 
 impl Url {
-	/** Very much todo! */
-	fn from_target(target: &[u8]) -> Option<Self> {
-		let mut query_pos: Option<usize> = None;
-		for (i, c) in target.iter().cloned().enumerate() {
-			if c == b'?' {
-				query_pos = Some(i);
+	/** Any URI-reference: absolute URIs, network-path, absolute-path and relative-path references */
+	pub fn parse(s: &str) -> Result<Url, ParseError> {
+		let mut url = Url::default();
+		let mut rest = s;
+
+		if let Some(i) = rest.find('#') {
+			let fragment = &rest[i + 1..];
+			validate(fragment, |c| is_pchar(c) || c == b'/' || c == b'?')?;
+			url.fragment = Some(fragment.to_string());
+			rest = &rest[..i];
+		}
+
+		if let Some(i) = rest.find('?') {
+			let query = &rest[i + 1..];
+			validate(query, |c| is_pchar(c) || c == b'/' || c == b'?')?;
+			url.query_string = Some(query.to_string());
+			rest = &rest[..i];
+		}
+
+		if let Some(end) = scheme_end(rest).filter(|end| !rest[..*end].contains('/')) {
+			url.scheme = Some(rest[..end].to_ascii_lowercase());
+			rest = &rest[end + 1..];
+		}
+
+		if let Some(after_slashes) = rest.strip_prefix("//") {
+			let end = after_slashes.find('/').unwrap_or(after_slashes.len());
+			let (userinfo, host, port) = parse_authority(&after_slashes[..end])?;
+			url.userinfo = userinfo;
+			url.host = Some(host);
+			url.port = port;
+			rest = &after_slashes[end..];
+		} else if url.scheme.is_none() {
+			// a relative-path reference can't look like it has a scheme
+			let first_segment = rest.split('/').next().unwrap_or_default();
+			if first_segment.contains(':') {
+				return Err(ParseError::InvalidUrl);
 			}
 		}
-		if query_pos.is_some() {
-			let pos = query_pos.unwrap();
-			Some(Self {
-				path: String::from_utf8_lossy(&target[..pos]).to_string(),
-				query_string: String::from_utf8_lossy(&target[pos..]).to_string(),
-				..Self::default()
-			})
+
+		validate(rest, |c| is_pchar(c) || c == b'/')?;
+		url.path = rest.to_string();
+
+		Ok(url)
+	}
+
+	/** `host:port`, the request target of CONNECT */
+	pub fn parse_authority(s: &str) -> Result<Url, ParseError> {
+		let (userinfo, host, port) = parse_authority(s)?;
+		if userinfo.is_some() || port.is_none() {
+			return Err(ParseError::InvalidUrl);
+		}
+
+		Ok(Url {
+			host: Some(host),
+			port,
+			..Url::default()
+		})
+	}
+
+	/** The four request-target forms of RFC 9112 3.2, chosen by `method` */
+	pub fn from_request_target(target: &[u8], method: &Method) -> Result<Url, ParseError> {
+		let target = std::str::from_utf8(target).map_err(|_| ParseError::InvalidUrl)?;
+
+		if *method == Method::CONNECT {
+			return Url::parse_authority(target);
+		}
+		if target == "*" {
+			return match method {
+				Method::OPTIONS => Ok(Url {
+					path: "*".to_string(),
+					..Url::default()
+				}),
+				_ => Err(ParseError::InvalidUrl),
+			};
+		}
+
+		// clients send `[]{}|` and the like unencoded in the query, only what can't
+		// be part of a request line, or would be a fragment, is turned down there
+		let (target, query) = match target.split_once('?') {
+			Some((target, query)) => (target, Some(query)),
+			None => (target, None),
+		};
+		if query.is_some_and(|q| q.bytes().any(|c| !c.is_ascii_graphic() || c == b'#')) {
+			return Err(ParseError::InvalidUrl);
+		}
+
+		let mut url = Url::parse(target)?;
+		url.query_string = query.map(str::to_string);
+		let origin_form = url.scheme.is_none() && url.host.is_none() && url.path.starts_with('/');
+		let absolute_form = url.scheme.is_some();
+
+		if url.fragment.is_some() || !(origin_form || absolute_form) {
+			return Err(ParseError::InvalidUrl);
+		}
+		Ok(url)
+	}
+
+	/** Resolves `reference` against `self` as the base, RFC 3986 5.2 */
+	pub fn join(&self, reference: &str) -> Result<Url, ParseError> {
+		let reference = Url::parse(reference)?;
+
+		if reference.scheme.is_some() {
+			return Ok(Url {
+				path: remove_dot_segments(&reference.path),
+				..reference
+			});
+		}
+
+		let mut target = Url {
+			fragment: reference.fragment.clone(),
+			..Url::default()
+		};
+		target.scheme = self.scheme.clone();
+
+		if reference.host.is_some() {
+			target.userinfo = reference.userinfo;
+			target.host = reference.host;
+			target.port = reference.port;
+			target.path = remove_dot_segments(&reference.path);
+			target.query_string = reference.query_string;
+			return Ok(target);
+		}
+
+		target.userinfo = self.userinfo.clone();
+		target.host = self.host.clone();
+		target.port = self.port;
+
+		if reference.path.is_empty() {
+			target.path = self.path.clone();
+			target.query_string = reference.query_string.or_else(|| self.query_string.clone());
 		} else {
-			Some(Self{
-				path: String::from_utf8_lossy(target).to_string(),
-				..Self::default()
+			target.path = if reference.path.starts_with('/') {
+				remove_dot_segments(&reference.path)
+			} else {
+				remove_dot_segments(&self.merge(&reference.path))
+			};
+			target.query_string = reference.query_string;
+		}
+
+		Ok(target)
+	}
+
+	/** RFC 3986 5.2.3 */
+	fn merge(&self, reference_path: &str) -> String {
+		if self.host.is_some() && self.path.is_empty() {
+			return format!("/{reference_path}");
+		}
+		match self.path.rfind('/') {
+			Some(i) => format!("{}{reference_path}", &self.path[..=i]),
+			None => reference_path.to_string(),
+		}
+	}
+
+	/** What goes between the method and the version: no fragment, `/` for an empty path */
+	pub fn request_target(&self) -> String {
+		let mut ret = String::new();
+		if let Some(scheme) = &self.scheme {
+			ret.push_str(scheme);
+			ret.push(':');
+		}
+		if let Some(authority) = self.authority() {
+			ret.push_str("//");
+			ret.push_str(&authority);
+		}
+		match self.path.as_str() {
+			"" => ret.push('/'),
+			path => ret.push_str(path),
+		}
+		if let Some(query) = &self.query_string {
+			ret.push('?');
+			ret.push_str(query);
+		}
+		ret
+	}
+
+	/** `[userinfo@]host[:port]`, e.g. for a Host header or a CONNECT request line */
+	pub fn authority(&self) -> Option<String> {
+		let host = self.host.as_ref()?;
+		let mut ret = String::new();
+		if let Some(userinfo) = &self.userinfo {
+			ret.push_str(userinfo);
+			ret.push('@');
+		}
+		ret.push_str(&host.to_string());
+		if let Some(port) = self.port {
+			ret.push_str(&format!(":{port}"));
+		}
+		Some(ret)
+	}
+
+	pub fn is_asterisk(&self) -> bool {
+		self.path == "*" && self.scheme.is_none() && self.host.is_none()
+	}
+
+	/** Dot segments removed, then percent-decoded; not safe for filesystem access, see [`safe_join`] */
+	pub fn decoded_path(&self) -> String {
+		percent_encoding::decode_str(&remove_dot_segments(&self.path))
+	}
+
+	/** Decoded segments of the normalized path, an encoded `/` stays inside its segment */
+	pub fn path_segments(&self) -> Vec<String> {
+		let path = remove_dot_segments(&self.path);
+		let path = path.strip_prefix('/').unwrap_or(&path);
+		if path.is_empty() {
+			return Vec::new();
+		}
+		path.split('/').map(percent_encoding::decode_str).collect()
+	}
+
+	/** Decoded with the form codec, so `+` is a space; duplicates kept in order */
+	pub fn query_pairs(&self) -> Vec<(String, String)> {
+		form_urlencoded::decode(self.query_string.as_deref().unwrap_or_default().as_bytes())
+	}
+
+	/** First value of `name` */
+	pub fn query_get(&self, name: &str) -> Option<String> {
+		self.query_pairs().into_iter().find(|(n, _)| n == name).map(|(_, v)| v)
+	}
+
+	pub fn query_get_all(&self, name: &str) -> Vec<String> {
+		self.query_pairs().into_iter().filter(|(n, _)| n == name).map(|(_, v)| v).collect()
+	}
+
+	/** Replaces every `name` with a single pair where the first one was, otherwise appends */
+	pub fn set_query(&mut self, name: &str, value: &str) -> &mut Self {
+		let mut pairs = self.raw_query_pairs();
+		let mut found = false;
+		pairs.retain_mut(|(n, raw)| {
+			if n != name {
+				return true;
+			}
+			if found {
+				return false;
+			}
+			found = true;
+			*raw = encode_query_pair(name, value);
+			true
+		});
+		if !found {
+			pairs.push((name.to_string(), encode_query_pair(name, value)));
+		}
+		self.set_raw_query_pairs(pairs);
+		self
+	}
+
+	pub fn append_query(&mut self, name: &str, value: &str) -> &mut Self {
+		let mut pairs = self.raw_query_pairs();
+		pairs.push((name.to_string(), encode_query_pair(name, value)));
+		self.set_raw_query_pairs(pairs);
+		self
+	}
+
+	/** Removes every `name`, returns their values */
+	pub fn remove_query(&mut self, name: &str) -> Vec<String> {
+		let removed = self.query_get_all(name);
+		if !removed.is_empty() {
+			let mut pairs = self.raw_query_pairs();
+			pairs.retain(|(n, _)| n != name);
+			self.set_raw_query_pairs(pairs);
+		}
+		removed
+	}
+
+	/** Pieces of the query as they are, with their decoded names; empty ones are dropped */
+	fn raw_query_pairs(&self) -> Vec<(String, String)> {
+		self.query_string.as_deref().unwrap_or_default()
+			.split('&')
+			.filter(|raw| !raw.is_empty())
+			.map(|raw| {
+				let name = raw.split('=').next().unwrap_or_default();
+				(form_urlencoded::decode_component(name.as_bytes()), raw.to_string())
 			})
+			.collect()
+	}
+
+	/** No pairs drop the `?` altogether */
+	fn set_raw_query_pairs(&mut self, pairs: Vec<(String, String)>) {
+		self.query_string = match pairs.is_empty() {
+			true => None,
+			false => Some(pairs.into_iter().map(|(_, raw)| raw).collect::<Vec<_>>().join("&")),
+		};
+	}
+}
+
+/** Only the pair being set is encoded, the rest of the query stays as it was */
+fn encode_query_pair(name: &str, value: &str) -> String {
+	let mut out = String::new();
+	form_urlencoded::encode_component(name, &mut out);
+	out.push('=');
+	form_urlencoded::encode_component(value, &mut out);
+	out
+}
+
+impl FromStr for Url {
+	type Err = ParseError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Url::parse(s)
+	}
+}
+
+impl Display for Url {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		if let Some(scheme) = &self.scheme {
+			write!(f, "{scheme}:")?;
+		}
+		if let Some(authority) = self.authority() {
+			write!(f, "//{authority}")?;
+		}
+		write!(f, "{}", self.path)?;
+		if let Some(query) = &self.query_string {
+			write!(f, "?{query}")?;
 		}
+		if let Some(fragment) = &self.fragment {
+			write!(f, "#{fragment}")?;
+		}
+		Ok(())
+	}
+}
+
+#[test]
+fn test_parse_url() {
+	let url = Url::parse("HTTP://user:pw@Example.com:8080/a/b?x=1&y#frag").unwrap();
+	assert_eq!(url.scheme.as_deref(), Some("http"));
+	assert_eq!(url.userinfo.as_deref(), Some("user:pw"));
+	assert_eq!(url.host, Some(Host::Domain("Example.com".to_string())));
+	assert_eq!(url.port, Some(8080));
+	assert_eq!(url.path, "/a/b");
+	assert_eq!(url.query_string.as_deref(), Some("x=1&y"));
+	assert_eq!(url.fragment.as_deref(), Some("frag"));
+
+	let url = Url::parse("http://[::1]:48001/").unwrap();
+	assert_eq!(url.host, Some(Host::Ipv6(Ipv6Addr::LOCALHOST)));
+	assert_eq!(url.port, Some(48001));
+
+	let url = Url::parse("http://127.0.0.1").unwrap();
+	assert_eq!(url.host, Some(Host::Ipv4(Ipv4Addr::LOCALHOST)));
+	assert_eq!(url.path, "");
+
+	let url = Url::parse("mailto:someone@example.com").unwrap();
+	assert_eq!(url.scheme.as_deref(), Some("mailto"));
+	assert_eq!(url.path, "someone@example.com");
+
+	assert_eq!(Url::parse("http://[::1/"), Err(ParseError::InvalidUrl));
+	assert_eq!(Url::parse("http://host:99999/"), Err(ParseError::InvalidUrl));
+	assert_eq!(Url::parse("/a b"), Err(ParseError::InvalidUrl));
+	assert_eq!(Url::parse("/a%2"), Err(ParseError::InvalidUrl));
+	assert_eq!(Url::parse("./a:b"), Ok(Url { path: "./a:b".to_string(), ..Url::default() }));
+}
+
+#[test]
+fn test_url_round_trip() {
+	for s in [
+		"http://user:pw@example.com:8080/a/b?x=1&y#frag",
+		"https://[2001:db8::7]/c=GB?objectClass?one",
+		"//example.com/network/path",
+		"/origin/form?q=%20",
+		"../relative?",
+		"urn:oasis:names:specification:docbook:dtd:xml:4.1.2",
+		"",
+	] {
+		assert_eq!(Url::parse(s).unwrap().to_string(), s);
 	}
 }
+
+#[test]
+fn test_request_target_forms() {
+	let url = Url::from_request_target(b"/where?q=now", &Method::GET).unwrap();
+	assert_eq!(url.path, "/where");
+
+	let url = Url::from_request_target(b"http://www.example.org/pub/", &Method::GET).unwrap();
+	assert_eq!(url.host, Some(Host::Domain("www.example.org".to_string())));
+
+	let url = Url::from_request_target(b"www.example.com:443", &Method::CONNECT).unwrap();
+	assert_eq!(url.authority().as_deref(), Some("www.example.com:443"));
+	assert_eq!(url.path, "");
+
+	let url = Url::from_request_target(b"*", &Method::OPTIONS).unwrap();
+	assert!(url.is_asterisk());
+
+	assert!(Url::from_request_target(b"*", &Method::GET).is_err());
+	assert!(Url::from_request_target(b"www.example.com", &Method::CONNECT).is_err());
+	assert!(Url::from_request_target(b"relative/path", &Method::GET).is_err());
+	assert!(Url::from_request_target(b"/with#fragment", &Method::GET).is_err());
+
+	// the query of a received target is only held to what a request line allows
+	let url = Url::from_request_target(b"/search?a[]=1&b={x}|y", &Method::GET).unwrap();
+	assert_eq!(url.query_string.as_deref(), Some("a[]=1&b={x}|y"));
+	assert_eq!(Url::parse("/search?a[]=1"), Err(ParseError::InvalidUrl));
+	assert!(Url::from_request_target(b"/search?q=a\x7fb", &Method::GET).is_err());
+	assert!(Url::from_request_target("/search?q=\u{e9}".as_bytes(), &Method::GET).is_err());
+	assert!(Url::from_request_target(b"/search?q#fragment", &Method::GET).is_err());
+	assert!(Url::from_request_target(b"/a[]?q", &Method::GET).is_err());
+}
+
+#[test]
+fn test_join() {
+	let base = Url::parse("http://a/b/c/d;p?q").unwrap();
+	for (reference, expected) in [
+		("g:h", "g:h"),
+		("g", "http://a/b/c/g"),
+		("./g", "http://a/b/c/g"),
+		("g/", "http://a/b/c/g/"),
+		("/g", "http://a/g"),
+		("//g", "http://g"),
+		("?y", "http://a/b/c/d;p?y"),
+		("g?y", "http://a/b/c/g?y"),
+		("#s", "http://a/b/c/d;p?q#s"),
+		("g?y#s", "http://a/b/c/g?y#s"),
+		(";x", "http://a/b/c/;x"),
+		("", "http://a/b/c/d;p?q"),
+		(".", "http://a/b/c/"),
+		("..", "http://a/b/"),
+		("../g", "http://a/b/g"),
+		("../../g", "http://a/g"),
+		("../../../g", "http://a/g"),
+		("/./g", "http://a/g"),
+		("g.", "http://a/b/c/g."),
+		("./g/.", "http://a/b/c/g/"),
+		("g;x=1/../y", "http://a/b/c/y"),
+	] {
+		assert_eq!(base.join(reference).unwrap().to_string(), expected, "{reference}");
+	}
+
+	let base = Url::parse("http://example.com").unwrap();
+	assert_eq!(base.join("next").unwrap().to_string(), "http://example.com/next");
+}
+
+#[test]
+fn test_decoded_path() {
+	let url = Url::parse("/files/./my%20docs/../a%2Fb/%C5%BC.txt").unwrap();
+	assert_eq!(url.decoded_path(), "/files/a/b/ż.txt");
+	assert_eq!(url.path_segments(), vec!["files", "a/b", "ż.txt"]);
+	assert!(Url::parse("/").unwrap().path_segments().is_empty());
+}
+
+#[test]
+fn test_query_pairs() {
+	let url = Url::parse("/search?q=rust+http&page=2").unwrap();
+	assert_eq!(url.path, "/search");
+	assert_eq!(
+		url.query_pairs(),
+		vec![("q".to_string(), "rust http".to_string()), ("page".to_string(), "2".to_string())]
+	);
+}
+
+#[test]
+fn test_query_access() {
+	let mut url = Url::parse("/posts?tag=a&page=1&tag=b%20c&flag").unwrap();
+	assert_eq!(url.query_get("page").as_deref(), Some("1"));
+	assert_eq!(url.query_get("flag").as_deref(), Some(""));
+	assert_eq!(url.query_get("missing"), None);
+	assert_eq!(url.query_get_all("tag"), vec!["a", "b c"]);
+
+	url.set_query("page", "2");
+	assert_eq!(url.to_string(), "/posts?tag=a&page=2&tag=b%20c&flag");
+
+	url.set_query("tag", "x&y").append_query("sort", "new");
+	assert_eq!(url.to_string(), "/posts?tag=x%26y&page=2&flag&sort=new");
+
+	assert_eq!(url.remove_query("tag"), vec!["x&y"]);
+	assert!(url.remove_query("tag").is_empty());
+	url.remove_query("page");
+	url.remove_query("flag");
+	url.remove_query("sort");
+	assert_eq!(url.to_string(), "/posts");
+
+	// pairs nobody touched keep their encoding, even one the form codec can't round-trip
+	let mut url = Url::parse("/?sig=%FF%00ab&x=1;y=2&flag&page=1").unwrap();
+	url.set_query("page", "2");
+	assert_eq!(url.query_string.as_deref(), Some("sig=%FF%00ab&x=1;y=2&flag&page=2"));
+	url.remove_query("flag");
+	assert_eq!(url.query_string.as_deref(), Some("sig=%FF%00ab&x=1;y=2&page=2"));
+}
@@ -0,0 +1,81 @@
+use std::path::{Path, PathBuf};
+use super::percent_encoding;
+
+/** RFC 3986 5.2.4, operates on the still encoded path */
+pub fn remove_dot_segments(path: &str) -> String {
+	let mut input = path;
+	let mut output: Vec<&str> = Vec::new();
+
+	while !input.is_empty() {
+		if let Some(rest) = input.strip_prefix("../").or_else(|| input.strip_prefix("./")) {
+			input = rest;
+		} else if input.starts_with("/./") {
+			input = &input[2..];
+		} else if input == "/." {
+			input = "/";
+		} else if input.starts_with("/../") || input == "/.." {
+			input = if input == "/.." { "/" } else { &input[3..] };
+			output.pop();
+		} else if input == "." || input == ".." {
+			input = "";
+		} else {
+			// move the first segment, including its leading '/', to the output
+			let start = usize::from(input.starts_with('/'));
+			let end = input[start..].find('/').map_or(input.len(), |i| start + i);
+			output.push(&input[..end]);
+			input = &input[end..];
+		}
+	}
+
+	output.concat()
+}
+
+/**
+ * Maps a request path onto a directory, `None` if the result would
+ * leave `root` or a decoded segment smuggles a separator or NUL
+ */
+pub fn safe_join(root: &Path, url_path: &str) -> Option<PathBuf> {
+	let mut segments: Vec<String> = Vec::new();
+
+	for raw in url_path.split('/') {
+		let segment = String::from_utf8(percent_encoding::decode(raw.as_bytes())).ok()?;
+		match segment.as_str() {
+			"" | "." => {}
+			".." => {
+				segments.pop()?;
+			}
+			s if s.contains(['/', '\\', '\0']) => return None,
+			// "C:" and alike would replace the root on Windows
+			s if Path::new(s).has_root() || s.contains(':') => return None,
+			_ => segments.push(segment),
+		}
+	}
+
+	let mut joined = root.to_path_buf();
+	joined.extend(segments);
+	Some(joined)
+}
+
+#[test]
+fn test_remove_dot_segments() {
+	assert_eq!(remove_dot_segments("/a/b/c/./../../g"), "/a/g");
+	assert_eq!(remove_dot_segments("mid/content=5/../6"), "mid/6");
+	assert_eq!(remove_dot_segments("/../../etc/passwd"), "/etc/passwd");
+	assert_eq!(remove_dot_segments("/a/b/.."), "/a/");
+	assert_eq!(remove_dot_segments("/a/./"), "/a/");
+	assert_eq!(remove_dot_segments("../"), "");
+	assert_eq!(remove_dot_segments("/%2E%2E/x"), "/%2E%2E/x");
+}
+
+#[test]
+fn test_safe_join() {
+	let root = Path::new("/srv/www");
+	assert_eq!(safe_join(root, "/"), Some(PathBuf::from("/srv/www")));
+	assert_eq!(safe_join(root, "/a/./b/../c%20d.txt"), Some(PathBuf::from("/srv/www/a/c d.txt")));
+	assert_eq!(safe_join(root, "/../etc/passwd"), None);
+	assert_eq!(safe_join(root, "/a/%2e%2e/%2E%2E/etc"), None);
+	assert_eq!(safe_join(root, "/a%2F..%2F..%2Fetc"), None);
+	assert_eq!(safe_join(root, "/a%5C..%5Cetc"), None);
+	assert_eq!(safe_join(root, "/a%00.txt"), None);
+	assert_eq!(safe_join(root, "/%FF"), None);
+}
@@ -0,0 +1,82 @@
+use super::{is_pchar, is_sub_delim, is_unreserved};
+
+/** Which bytes are left as they are, per RFC 3986 component grammar; everything else becomes `%XX` */
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum EncodeSet {
+	/** unreserved only, safe anywhere */
+	Component,
+	Userinfo,
+	/** a whole path, `/` kept */
+	Path,
+	/** a single segment, `/` encoded */
+	PathSegment,
+	Query,
+	Fragment,
+}
+
+impl EncodeSet {
+	fn keeps(&self, c: u8) -> bool {
+		match self {
+			EncodeSet::Component => is_unreserved(c),
+			EncodeSet::Userinfo => is_unreserved(c) || is_sub_delim(c) || c == b':',
+			EncodeSet::Path => is_pchar(c) || c == b'/',
+			EncodeSet::PathSegment => is_pchar(c),
+			EncodeSet::Query | EncodeSet::Fragment => is_pchar(c) || c == b'/' || c == b'?',
+		}
+	}
+}
+
+pub(crate) fn hex_value(c: u8) -> Option<u8> {
+	(c as char).to_digit(16).map(|d| d as u8)
+}
+
+pub fn encode(input: &str, set: EncodeSet) -> String {
+	let mut out = String::with_capacity(input.len());
+	for c in input.bytes() {
+		if set.keeps(c) {
+			out.push(c as char);
+		} else {
+			out.push_str(&format!("%{c:02X}"));
+		}
+	}
+	out
+}
+
+/** Malformed `%` escapes are kept as they are */
+pub fn decode(input: &[u8]) -> Vec<u8> {
+	let mut out = Vec::with_capacity(input.len());
+	let mut i = 0;
+	while i < input.len() {
+		match (input[i], input.get(i + 1).and_then(|c| hex_value(*c)),
+			   input.get(i + 2).and_then(|c| hex_value(*c))) {
+			(b'%', Some(h), Some(l)) => {
+				out.push(h << 4 | l);
+				i += 3;
+			}
+			(c, _, _) => {
+				out.push(c);
+				i += 1;
+			}
+		}
+	}
+	out
+}
+
+/** Invalid UTF-8 is replaced, see [`String::from_utf8_lossy`] */
+pub fn decode_str(input: &str) -> String {
+	String::from_utf8_lossy(&decode(input.as_bytes())).to_string()
+}
+
+#[test]
+fn test_percent_encoding() {
+	assert_eq!(encode("a b/c?d", EncodeSet::Path), "a%20b/c%3Fd");
+	assert_eq!(encode("a b/c?d", EncodeSet::PathSegment), "a%20b%2Fc%3Fd");
+	assert_eq!(encode("k=v&w/?", EncodeSet::Query), "k=v&w/?");
+	assert_eq!(encode("user:pw@", EncodeSet::Userinfo), "user:pw%40");
+	assert_eq!(encode("zażółć", EncodeSet::Component), "za%C5%BC%C3%B3%C5%82%C4%87");
+
+	assert_eq!(decode_str("za%C5%BC%c3%b3%C5%82%C4%87"), "zażółć");
+	assert_eq!(decode(b"100%"), b"100%");
+	assert_eq!(decode(b"%zz%4"), b"%zz%4");
+	assert_eq!(decode_str("%FF"), "\u{FFFD}");
+}
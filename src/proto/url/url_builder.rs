@@ -0,0 +1,105 @@
+use super::percent_encoding::{encode, EncodeSet};
+use super::{Host, Url};
+
+/** Components are given decoded and escaped as they're set */
+#[derive(Debug, Clone, Default)]
+pub struct UrlBuilder {
+	url: Url,
+	segments: Vec<String>,
+	trailing_slash: bool,
+}
+
+impl UrlBuilder {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn into_url(self) -> Url {
+		let mut url = self.url;
+
+		if !self.segments.is_empty() || url.host.is_none() || self.trailing_slash {
+			url.path = format!("/{}", self.segments.join("/"));
+			if self.trailing_slash && !self.segments.is_empty() {
+				url.path.push('/');
+			}
+		}
+
+		url
+	}
+
+	/** Lowercased, as schemes are case-insensitive */
+	pub fn set_scheme(&mut self, scheme: &str) -> &mut Self {
+		self.url.scheme = Some(scheme.to_ascii_lowercase());
+		self
+	}
+
+	pub fn set_userinfo(&mut self, user: &str, password: Option<&str>) -> &mut Self {
+		let mut userinfo = encode(user, EncodeSet::Component);
+		if let Some(password) = password {
+			userinfo.push(':');
+			userinfo.push_str(&encode(password, EncodeSet::Component));
+		}
+		self.url.userinfo = Some(userinfo);
+		self
+	}
+
+	pub fn set_host(&mut self, host: Host) -> &mut Self {
+		self.url.host = Some(host);
+		self
+	}
+
+	pub fn set_port(&mut self, port: u16) -> &mut Self {
+		self.url.port = Some(port);
+		self
+	}
+
+	/** `/` inside `segment` is escaped, it never adds a level */
+	pub fn push_segment(&mut self, segment: &str) -> &mut Self {
+		self.segments.push(encode(segment, EncodeSet::PathSegment));
+		self
+	}
+
+	/** Ends the path with `/`, i.e. a directory */
+	pub fn set_trailing_slash(&mut self, trailing_slash: bool) -> &mut Self {
+		self.trailing_slash = trailing_slash;
+		self
+	}
+
+	pub fn append_query(&mut self, name: &str, value: &str) -> &mut Self {
+		self.url.append_query(name, value);
+		self
+	}
+
+	pub fn set_fragment(&mut self, fragment: &str) -> &mut Self {
+		self.url.fragment = Some(encode(fragment, EncodeSet::Fragment));
+		self
+	}
+}
+
+#[test]
+fn test_url_builder() {
+	let mut builder = UrlBuilder::new();
+	builder
+		.set_scheme("HTTPS")
+		.set_host(Host::Domain("example.com".to_string()))
+		.set_port(8443)
+		.push_segment("files")
+		.push_segment("a/b c.txt")
+		.append_query("q", "rust & http")
+		.append_query("tag", "a")
+		.append_query("tag", "b");
+
+	let url = builder.into_url();
+	assert_eq!(url.to_string(), "https://example.com:8443/files/a%2Fb%20c.txt?q=rust+%26+http&tag=a&tag=b");
+	assert_eq!(Url::parse(&url.to_string()), Ok(url));
+
+	let mut builder = UrlBuilder::new();
+	builder
+		.set_scheme("http")
+		.set_userinfo("me@home", Some("p:w"))
+		.set_host("::1".parse().unwrap())
+		.set_fragment("top");
+	assert_eq!(builder.into_url().to_string(), "http://me%40home:p%3Aw@[::1]#top");
+
+	assert_eq!(UrlBuilder::new().into_url().request_target(), "/");
+}
@@ -2,3 +2,204 @@
 // fn first_as_client() {
 // 	let request = http::
 // }
+
+#[test]
+fn request_round_trip() {
+	use http::consts::Method;
+
+	let mut builder = http::request::Builder::new();
+	builder
+		.set_method(Method::PUT)
+		.set_target("/files/a.txt").unwrap()
+		.push_header("Host", "localhost")
+		.set_body(b"hello");
+	let bytes = builder.into_request().into_bytes().unwrap();
+
+	let mut collector = http::request::Collector::new();
+	collector.push_bytes(&bytes);
+	assert!(collector.is_finished());
+
+	let request = collector.into_request().unwrap();
+	assert_eq!(request.method(), &Method::PUT);
+	assert_eq!(request.url().path, "/files/a.txt");
+	assert_eq!(request.message().body(), b"hello");
+}
+
+#[test]
+fn untrusted_bytes_are_errors_not_panics() {
+	use http::ParseError;
+
+	let mut collector = http::request::Collector::new();
+	collector.push_bytes(b"GET / HTTP/1.1\r\nUser-Agent: caf\xc3\xa9 \xff\r\n\r\n");
+	assert!(collector.is_finished());
+	assert_eq!(collector.push_bytes(b"GET / HTTP/1.1\r\n\r\n"), 0);
+	let request = collector.into_request().unwrap();
+	assert_eq!(request.message().headers().get_bytes("user-agent"), Some(&b"caf\xc3\xa9 \xff"[..]));
+
+	let mut collector = http::request::Collector::new();
+	collector.push_bytes(b"GET / HTTP/1.1\r\n\xc3\xa9: x\r\n\r\n");
+	let error = collector.into_request().unwrap_err();
+	assert_eq!(error.kind(), ParseError::InvalidTokenChar);
+	assert_eq!(error.offset(), 16);
+
+	let mut collector = http::request::Collector::new();
+	collector.push_bytes(b"GET / HTTP/1.1\r\nHost: x\r\n");
+	assert_eq!(collector.into_request().unwrap_err().kind(), ParseError::IncompleteMessage);
+}
+
+#[test]
+fn request_limits() {
+	use http::config::CollectorLimits;
+	use http::consts::StatusCode;
+
+	let limits = CollectorLimits {
+		max_body: 8,
+		..CollectorLimits::default()
+	};
+	let mut collector = http::request::Collector::new().with_limits(limits);
+	collector.push_bytes(b"POST /upload HTTP/1.1\r\nContent-Length: 100\r\n\r\n");
+	let error = collector.into_request().unwrap_err();
+	assert_eq!(error.kind().status_code(), StatusCode::CONTENT_TOO_LARGE);
+
+	// an endless header line is cut off without waiting for its end
+	let mut collector = http::request::Collector::new();
+	collector.push_bytes(b"GET / HTTP/1.1\r\nX-Endless: ");
+	for _ in 0..100 {
+		collector.push_bytes(&[b'a'; 1024]);
+	}
+	assert!(collector.is_finished());
+	let error = collector.into_request().unwrap_err();
+	assert_eq!(error.kind().status_code(), StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE);
+
+	// a body past the preamble limit, pushed along with the headers, is only a body
+	let mut collector = http::request::Collector::new();
+	collector.push_bytes(&[b"POST /upload HTTP/1.1\r\nContent-Length: 70000\r\n\r\n".as_slice(), &[b'a'; 70000]].concat());
+	assert_eq!(collector.into_request().unwrap().message().body().len(), 70000);
+
+	let mut connection = http::connection::Connection::new();
+	connection.push_bytes(&b"GET / HTTP/1.1\r\n\r\n".repeat(5000));
+	assert_eq!(connection.filter(|r| r.is_ok()).count(), 5000);
+}
+
+#[test]
+fn parser_profiles() {
+	use http::config::ParserConfig;
+	use http::ParseError;
+
+	let legacy = b"http/1.0  200\nServer: device\n\nhello";
+
+	let mut collector = http::response::Collector::new().with_config(ParserConfig::strict());
+	collector.push_bytes(legacy);
+	collector.signal_connection_close();
+	assert_eq!(collector.into_response().unwrap_err().kind(), ParseError::BareLF);
+
+	let mut collector = http::response::Collector::new();
+	collector.push_bytes(legacy);
+	collector.signal_connection_close();
+	let response = collector.into_response().unwrap();
+	assert_eq!(response.status_code().as_u16(), 200);
+	assert_eq!(response.message().body(), b"hello");
+}
+
+#[test]
+fn collected_content_type() {
+	use http::consts::MimeType;
+
+	let mut collector = http::request::Collector::new();
+	collector.push_bytes(
+		b"POST /upload HTTP/1.1\r\n\
+		Content-Type: multipart/form-data; boundary=----x\r\n\r\n"
+	);
+	let request = collector.into_request().unwrap();
+
+	let content_type = request.message().content_type().unwrap();
+	assert_eq!(content_type.mime_type(), MimeType::Multipart);
+	assert_eq!(content_type.boundary(), Some("----x"));
+}
+
+#[test]
+fn multipart_upload_round_trip() {
+	use http::media_type::MediaType;
+	use http::multipart::MultipartBuilder;
+
+	let mut multipart = MultipartBuilder::new();
+	multipart
+		.add_text("text-field", "hello")
+		.add_file("file", "data.bin", &MediaType::new("application", "octet-stream"), &[0, 1, 2]);
+
+	let mut builder = http::request::Builder::new();
+	builder
+		.set_method(http::consts::Method::POST)
+		.set_target("/file-form-result").unwrap()
+		.set_multipart(multipart.build().unwrap());
+	let bytes = builder.into_request().into_bytes().unwrap();
+
+	let mut collector = http::request::Collector::new();
+	collector.push_bytes(&bytes);
+	let request = collector.into_request().unwrap();
+
+	let parts = request.message().multipart().unwrap();
+	assert_eq!(parts.len(), 2);
+	assert_eq!(parts[0].name(), Some("text-field"));
+	assert_eq!(parts[0].body(), b"hello");
+	assert_eq!(parts[1].filename(), Some("data.bin"));
+	assert_eq!(parts[1].body(), &[0, 1, 2]);
+}
+
+#[test]
+fn multipart_upload_streamed_to_disk() {
+	use http::multipart::MultipartSpooler;
+
+	let file_contents = vec![b'x'; 100_000];
+	let mut body = Vec::new();
+	body.extend_from_slice(b"--b\r\nContent-Disposition: form-data; name=\"file\"; filename=\"x\"\r\n\r\n");
+	body.extend_from_slice(&file_contents);
+	body.extend_from_slice(b"\r\n--b--\r\n");
+
+	let mut bytes = format!(
+		"POST /upload HTTP/1.1\r\n\
+		Content-Type: multipart/form-data; boundary=b\r\n\
+		Content-Length: {}\r\n\r\n",
+		body.len()
+	).into_bytes();
+	bytes.extend_from_slice(&body);
+
+	let mut collector = http::request::Collector::new();
+	let mut spooler = MultipartSpooler::new("b").unwrap();
+	for piece in bytes.chunks(4096) {
+		collector.push_bytes(piece);
+		spooler.push(&collector.take_body()).unwrap();
+	}
+	assert!(collector.is_finished());
+	assert!(collector.into_request().unwrap().message().body().is_empty());
+
+	let result = spooler.finish().unwrap();
+	assert_eq!(result.files[0].size, file_contents.len() as u64);
+	assert_eq!(std::fs::read(&result.files[0].path).unwrap(), file_contents);
+	std::fs::remove_file(&result.files[0].path).unwrap();
+}
+
+#[test]
+fn pipelined_requests_hand_off_leftover_bytes() {
+	let mut collector = http::request::Collector::new();
+	assert_eq!(collector.push_bytes(b"POST /a HTTP/1.1\r\nContent-Length: 3\r\n"), 37);
+	assert_eq!(collector.push_bytes(b"\r\nabcGET /b HTTP/1.1\r\n"), 5);
+	assert!(collector.is_finished());
+
+	let (request, remaining) = collector.finish().unwrap();
+	assert_eq!(request.message().body(), b"abc");
+	assert_eq!(remaining, b"GET /b HTTP/1.1\r\n");
+
+	// one read carrying a request and a half
+	let mut connection = http::connection::Connection::new();
+	connection.push_bytes(&remaining);
+	connection.push_bytes(b"\r\nGET /c HTTP/1.1\r\n\r\nGET /d HTTP/1.1\r\n");
+	let paths: Vec<String> = connection.by_ref()
+		.map(|r| r.unwrap().url().path.clone())
+		.collect();
+	assert_eq!(paths, vec!["/b", "/c"]);
+
+	connection.push_bytes(b"\r\n");
+	assert_eq!(connection.next().unwrap().unwrap().url().path, "/d");
+	assert!(connection.next().is_none());
+}
@@ -0,0 +1,145 @@
+//! Known request smuggling payloads, each of which some parser out there
+//! reads with different framing than another one.
+
+use http::config::ParserConfig;
+use http::ParseError;
+
+fn collect(config: ParserConfig, input: &[u8]) -> Result<http::request::Request, http::CollectError> {
+	let mut collector = http::request::Collector::new().with_config(config);
+	collector.push_bytes(input);
+	collector.into_request()
+}
+
+fn rejected_with(input: &[u8]) -> ParseError {
+	match collect(ParserConfig::default(), input) {
+		Ok(_) => panic!("accepted {:?}", String::from_utf8_lossy(input)),
+		Err(e) => e.kind(),
+	}
+}
+
+#[test]
+fn cl_te() {
+	assert_eq!(rejected_with(
+		b"POST / HTTP/1.1\r\nHost: a\r\nContent-Length: 13\r\nTransfer-Encoding: chunked\r\n\r\n\
+		0\r\n\r\nSMUGGLED"
+	), ParseError::ConflictingFraming);
+
+	assert_eq!(rejected_with(
+		b"POST / HTTP/1.1\r\nHost: a\r\nTransfer-Encoding: chunked\r\nContent-Length: 4\r\n\r\n\
+		5c\r\nGPOST / HTTP/1.1\r\n\r\n0\r\n\r\n"
+	), ParseError::ConflictingFraming);
+
+	assert_eq!(rejected_with(
+		b"POST / HTTP/1.1\r\nHost: a\r\nTransfer-Encoding: \r\nContent-Length: 5\r\n\r\nhello"
+	), ParseError::ConflictingFraming);
+
+	// an HTTP/1.0 hop in between may well go by the Content-Length, or by nothing
+	assert_eq!(rejected_with(
+		b"POST / HTTP/1.0\r\nHost: a\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n"
+	), ParseError::TransferEncodingInHttp10);
+}
+
+#[test]
+fn te_te_obfuscation() {
+	for te in [
+		&b"Transfer-Encoding: xchunked"[..],
+		b"Transfer-Encoding: chunked, identity",
+		b"Transfer-Encoding: chunked\r\nTransfer-Encoding: x",
+		b"Transfer-Encoding: chunked, chunked",
+		b"Transfer-Encoding: \"chunked\"",
+		b"Transfer-Encoding: ",
+		b"Transfer-Encoding: ,",
+	] {
+		let input = [b"POST / HTTP/1.1\r\nHost: a\r\n", te, b"\r\n\r\n0\r\n\r\n"].concat();
+		assert!(matches!(
+			rejected_with(&input),
+			ParseError::ChunkedNotFinal | ParseError::UnsupportedTransferEncoding
+		), "{:?}", String::from_utf8_lossy(te));
+	}
+
+	assert_eq!(rejected_with(
+		b"POST / HTTP/1.1\r\nTransfer-Encoding : chunked\r\n\r\n0\r\n\r\n"
+	), ParseError::WhitespaceBeforeColon);
+
+	assert_eq!(rejected_with(
+		b"POST / HTTP/1.1\r\nHost: a\r\n Transfer-Encoding: chunked\r\n\r\n0\r\n\r\n"
+	), ParseError::ObsFold);
+
+	assert_eq!(rejected_with(
+		b"POST / HTTP/1.1\r\nHost: a\r\nTransfer-Encoding\x0b: chunked\r\n\r\n0\r\n\r\n"
+	), ParseError::InvalidTokenChar);
+}
+
+#[test]
+fn content_length_confusion() {
+	assert_eq!(rejected_with(
+		b"POST / HTTP/1.1\r\nContent-Length: 4\r\nContent-Length: 5\r\n\r\nabcde"
+	), ParseError::ConflictingContentLength);
+
+	assert_eq!(rejected_with(
+		b"POST / HTTP/1.1\r\nContent-Length: 4, 5\r\n\r\nabcde"
+	), ParseError::ConflictingContentLength);
+
+	for value in [&b"+5"[..], b"-5", b"0x5", b"5 5", b"5.0", b""] {
+		let input = [b"POST / HTTP/1.1\r\nContent-Length: ", value, b"\r\n\r\nabcde"].concat();
+		assert_eq!(rejected_with(&input), ParseError::InvalidContentLength, "{:?}", value);
+	}
+}
+
+#[test]
+fn line_endings() {
+	assert_eq!(rejected_with(
+		b"POST / HTTP/1.1\nContent-Length: 5\n\nabcde"
+	), ParseError::BareLF);
+
+	assert_eq!(rejected_with(
+		b"POST / HTTP/1.1\r\nHost: a\nContent-Length: 5\r\n\r\nabcde"
+	), ParseError::BareLF);
+
+	assert_eq!(rejected_with(
+		b"POST / HTTP/1.1\r\nX: y\rContent-Length: 5\r\n\r\nabcde"
+	), ParseError::BareCR);
+
+	assert_eq!(rejected_with(
+		b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\nabcde\r\n0\r\n\r\n"
+	), ParseError::BareLF);
+}
+
+#[test]
+fn chunked_body_tricks() {
+	assert_eq!(rejected_with(
+		b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n0x5\r\nabcde\r\n0\r\n\r\n"
+	), ParseError::InvalidChunkSize);
+
+	assert_eq!(rejected_with(
+		b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nabcdeXX0\r\n\r\n"
+	), ParseError::InvalidChunkDelimiter);
+
+	assert_eq!(rejected_with(
+		b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5 ;\x01\r\nabcde\r\n0\r\n\r\n"
+	), ParseError::InvalidChunkSize);
+}
+
+#[test]
+fn lenient_config_resolves_instead() {
+	let request = collect(
+		ParserConfig::lenient(),
+		b"POST / HTTP/1.1\nContent-Length: 13\nTransfer-Encoding : chunked\n\n3\nabc\n0\n\n",
+	).unwrap();
+	assert_eq!(request.message().body(), b"abc");
+
+	// let through, but what follows can't be trusted
+	let mut collector = http::request::Collector::new().with_config(ParserConfig::lenient());
+	collector.push_bytes(b"POST / HTTP/1.0\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n0\r\n\r\n");
+	assert!(collector.must_close());
+	assert_eq!(collector.into_request().unwrap().message().body(), b"abc");
+
+	// what stays an error whatever the config
+	for input in [
+		&b"POST / HTTP/1.1\r\nContent-Length: 4\r\nContent-Length: 5\r\n\r\nabcde"[..],
+		b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked, gzip\r\n\r\n",
+		b"POST / HTTP/1.1\r\nX: y\rZ: w\r\n\r\n",
+	] {
+		assert!(collect(ParserConfig::lenient(), input).is_err());
+	}
+}